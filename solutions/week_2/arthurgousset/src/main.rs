@@ -1,48 +1,102 @@
-use num_bigint::BigUint;
-use num_traits::ToPrimitive;
-use primes::{PrimeSet, Sieve};
-use rand::prelude::*;
+use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
+use num_traits::{One, Zero};
+use rand::seq::SliceRandom;
+use std::fmt;
+
+/// Primes of increasing size to pick the finite field from: 2^61 - 1, 2^89 - 1, 2^127 - 1,
+/// 2^255 - 19 and 2^521 - 1.
+const PRIMES: [(u64, &str); 5] = [
+    (61, "2305843009213693951"),
+    (89, "618970019642690137449562111"),
+    (127, "170141183460469231731687303715884105727"),
+    (255, "57896044618658097711785492504343953926634992332820282019728792003956564819949"),
+    (521, "6864797660130609714981900799081393217269435300143305409394463459185543183397656052122559640661454554977296311391480858037121987999716643812574028291115057151"),
+];
 
 fn main() {
-    let secret = 1559; // A random integer I chose manually for simplicity
+    let secret = BigUint::from(1559u32); // A random integer I chose manually for simplicity
     println!("Secret is: {}", secret);
 
-    let shares = 6;
-    println!("Number of shares are: {}", shares);
+    let nr_shares = 6;
+    let threshold = 3;
+    println!("Number of shares are: {}", nr_shares);
+    println!("Threshold is: {}", threshold);
+
+    let security_level = 128;
+    let prime = prime_for_security_level(security_level);
+    println!("Prime for {}-bit security is: {}", security_level, prime);
 
-    let prime = generate_prime_number_greater_than(secret);
-    let secret_shares = generate_secret_shares(secret, shares, prime);
+    let secret_shares = generate_secret_shares(&secret, threshold, nr_shares, &prime).unwrap();
     println!("Secret shares: {:?}", secret_shares);
 
-    let reconstructed_secret = lagrange_interpolation(&secret_shares, prime);
+    // Any `threshold` of the shares are enough
+    let mut rng = rand::thread_rng();
+    let subset: Vec<(BigUint, BigUint)> = secret_shares
+        .choose_multiple(&mut rng, threshold)
+        .cloned()
+        .collect();
+    println!(
+        "Using shares: {:?}",
+        subset.iter().map(|(x, _)| x).collect::<Vec<_>>()
+    );
+
+    let reconstructed_secret = lagrange_interpolation(&subset, threshold, &prime).unwrap();
     println!("Reconstructed secret: {}", reconstructed_secret);
+
+    // One share fewer than the threshold would interpolate an unrelated value, so it is refused
+    let too_few = &subset[..threshold.saturating_sub(1)];
+    if let Err(error) = lagrange_interpolation(too_few, threshold, &prime) {
+        println!("Reconstruction from {} shares failed: {}", too_few.len(), error);
+    }
+
+    // Invalid share sets are reported instead of ending the process
+    let duplicated = [subset[0].clone(), subset[0].clone(), subset[1].clone()];
+    match lagrange_interpolation(&duplicated, threshold, &prime) {
+        Ok(value) => println!("Reconstructed from duplicated shares: {}", value),
+        Err(error) => println!("Reconstruction from duplicated shares failed: {}", error),
+    }
+
+    // Shares can also sit at participant IDs instead of 1..=n
+    let participant_ids: Vec<BigUint> = [1001u32, 2002, 3003, 4004].map(BigUint::from).to_vec();
+    let id_shares =
+        generate_secret_shares_at(&secret, threshold, &participant_ids, &prime).unwrap();
+    println!(
+        "Reconstructed from shares of IDs {:?}: {}",
+        id_shares[1..].iter().map(|(x, _)| x).collect::<Vec<_>>(),
+        lagrange_interpolation(&id_shares[1..], threshold, &prime).unwrap()
+    );
+
+    // A repeated participant ID is reported instead of panicking
+    let repeated_ids = [
+        participant_ids[0].clone(),
+        participant_ids[0].clone(),
+        participant_ids[1].clone(),
+    ];
+    if let Err(error) = generate_secret_shares_at(&secret, threshold, &repeated_ids, &prime) {
+        println!("Sharing at IDs {:?} failed: {}", repeated_ids, error);
+    }
 }
 
-fn generate_prime_number_greater_than(lower_bound: u64) -> u64 {
-    let mut pset = Sieve::new();
-    let seed = rand::thread_rng().gen_range(lower_bound..lower_bound + 100);
-    let primes = pset
+/// Smallest prime in `PRIMES` with at least `security_level` bits.
+fn prime_for_security_level(security_level: u64) -> BigUint {
+    let (_, prime) = PRIMES
         .iter()
-        .skip(seed.try_into().unwrap())
-        .take(2)
-        .collect::<Vec<_>>();
-    let prime = primes[0];
-    println!("Random prime number is: {}", prime);
-    prime
+        .find(|(bits, _)| *bits >= security_level)
+        .unwrap_or_else(|| panic!("No prime for a security level of {} bits", security_level));
+    BigUint::parse_bytes(prime.as_bytes(), 10).unwrap()
 }
 
-fn generate_polynomial(secret: u64, nr_shares: u64, prime: u64) -> Vec<u64> {
-    // TODO: prime should be BigInt
-    let mut coefficients = Vec::with_capacity(nr_shares as usize);
+fn generate_polynomial(secret: &BigUint, threshold: usize, prime: &BigUint) -> Vec<BigUint> {
+    let mut coefficients = Vec::with_capacity(threshold);
 
     // The first coefficient of the polynomial is the secret
-    coefficients.push(secret);
+    coefficients.push(secret.clone());
 
-    // Generate random coefficients for the remaining terms
+    // Generate random coefficients for the remaining terms, the polynomial has degree threshold - 1
     let mut rng = rand::thread_rng();
-    for _ in 1..nr_shares {
+    for _ in 1..threshold {
         // Generate a random number in the range [0, prime)
-        let random_coefficient = rng.gen_range(0..prime);
+        let random_coefficient = rng.gen_biguint_below(prime);
         coefficients.push(random_coefficient);
     }
 
@@ -51,102 +105,262 @@ fn generate_polynomial(secret: u64, nr_shares: u64, prime: u64) -> Vec<u64> {
     coefficients
 }
 
-fn evaluate_polynomial(coefficients: &[u64], x: u64, prime: u64) -> u64 {
-    let mut y = 0;
-    for (i, &coefficient) in coefficients.iter().enumerate() {
-        y += coefficient * x.pow(i.try_into().unwrap());
-        y %= prime; // Calculate modulo prime to ensure values stay in the finite field
+/// Horner's rule: f(x) = a_0 + x(a_1 + x(a_2 + ...)), reduced modulo prime at every step.
+fn evaluate_polynomial(coefficients: &[BigUint], x: &BigUint, prime: &BigUint) -> BigUint {
+    let mut y = BigUint::zero();
+    for coefficient in coefficients.iter().rev() {
+        y = (y * x + coefficient) % prime;
     }
     y
 }
 
-fn generate_secret_shares(secret: u64, nr_shares: u64, prime: u64) -> Vec<(u64, u64)> {
-    let polynomial_coefficients = generate_polynomial(secret, nr_shares, prime);
-    let mut secret_shares: Vec<(u64, u64)> = Vec::with_capacity(nr_shares as usize);
+fn generate_secret_shares(
+    secret: &BigUint,
+    threshold: usize,
+    nr_shares: usize,
+    prime: &BigUint,
+) -> Result<Vec<(BigUint, BigUint)>, ShamirError> {
+    let xs: Vec<BigUint> = (1..=nr_shares).map(BigUint::from).collect();
+    generate_secret_shares_at(secret, threshold, &xs, prime)
+}
 
-    for i in 1..nr_shares+1 {
-        let x = i;
-        let y = evaluate_polynomial(&polynomial_coefficients, x, prime);
-        secret_shares.push((x, y));
+/// Shares at caller-chosen x-coordinates, e.g. participant IDs. Zero would give away the secret,
+/// a repeated x can't be interpolated and an x from outside the field is ambiguous, so all three
+/// are returned as errors.
+fn generate_secret_shares_at(
+    secret: &BigUint,
+    threshold: usize,
+    xs: &[BigUint],
+    prime: &BigUint,
+) -> Result<Vec<(BigUint, BigUint)>, ShamirError> {
+    if threshold == 0 || threshold > xs.len() {
+        return Err(ShamirError::InvalidThreshold { threshold, nr_shares: xs.len() });
+    }
+    if secret >= prime {
+        return Err(ShamirError::SecretOutOfRange);
+    }
+    for (i, x) in xs.iter().enumerate() {
+        if x >= prime {
+            return Err(ShamirError::ValueOutOfRange(x.clone()));
+        }
+        if x.is_zero() {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if xs[..i].contains(x) {
+            return Err(ShamirError::DuplicateIndex(x.clone()));
+        }
+    }
+
+    let polynomial_coefficients = generate_polynomial(secret, threshold, prime);
+    Ok(xs
+        .iter()
+        .map(|x| {
+            let y = evaluate_polynomial(&polynomial_coefficients, x, prime);
+            (x.clone(), y)
+        })
+        .collect())
+}
+
+/// Why the shares could not be created or interpolated.
+#[derive(Debug, PartialEq, Eq)]
+enum ShamirError {
+    /// Fewer shares than the threshold, which would interpolate an unrelated value.
+    NotEnoughShares { have: usize, need: usize },
+    /// The threshold is zero or larger than the number of shares.
+    InvalidThreshold { threshold: usize, nr_shares: usize },
+    /// The secret is not smaller than the prime.
+    SecretOutOfRange,
+    /// A share at x = 0 would be the secret itself.
+    ZeroIndex,
+    DuplicateIndex(BigUint),
+    /// A share coordinate is not smaller than the prime, so it belongs to a different field.
+    ValueOutOfRange(BigUint),
+    /// A denominator has no inverse, which only happens if the modulus is not prime.
+    NotInvertible(BigUint),
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShamirError::NotEnoughShares { have, need } => {
+                write!(f, "{} shares given but {} are needed", have, need)
+            }
+            ShamirError::InvalidThreshold { threshold, nr_shares } => write!(
+                f,
+                "Threshold {} must be between 1 and the number of shares {}",
+                threshold, nr_shares
+            ),
+            ShamirError::SecretOutOfRange => write!(f, "Secret must be smaller than the prime"),
+            ShamirError::ZeroIndex => write!(f, "A share has x-coordinate 0"),
+            ShamirError::DuplicateIndex(x) => write!(f, "More than one share has x = {}", x),
+            ShamirError::ValueOutOfRange(value) => {
+                write!(f, "Share value {} is not smaller than the prime", value)
+            }
+            ShamirError::NotInvertible(denominator) => write!(
+                f,
+                "No modular multiplicative inverse exists for {}",
+                denominator
+            ),
+        }
     }
-    secret_shares
 }
 
-fn lagrange_interpolation(shares: &[(u64, u64)], prime: u64) -> u64 {
-    let mut secret = 0;
+impl std::error::Error for ShamirError {}
 
-    for (i, &(x_i, y_i)) in shares.iter().enumerate() {
-        let mut numerator = 1;
-        let mut denominator = 1;
+/// Lagrange interpolation at x = 0, works with any `threshold` of the shares and uses the first
+/// `threshold` given.
+fn lagrange_interpolation(
+    shares: &[(BigUint, BigUint)],
+    threshold: usize,
+    prime: &BigUint,
+) -> Result<BigUint, ShamirError> {
+    if threshold == 0 {
+        return Err(ShamirError::InvalidThreshold { threshold, nr_shares: shares.len() });
+    }
+    if shares.len() < threshold {
+        return Err(ShamirError::NotEnoughShares { have: shares.len(), need: threshold });
+    }
+    let shares = &shares[..threshold];
+    for (i, (x, y)) in shares.iter().enumerate() {
+        if let Some(value) = [x, y].into_iter().find(|value| *value >= prime) {
+            return Err(ShamirError::ValueOutOfRange(value.clone()));
+        }
+        if x.is_zero() {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if shares[..i].iter().any(|(other, _)| other == x) {
+            return Err(ShamirError::DuplicateIndex(x.clone()));
+        }
+    }
+
+    let mut secret = BigUint::zero();
+
+    for (i, (x_i, y_i)) in shares.iter().enumerate() {
+        let mut numerator = BigUint::one();
+        let mut denominator = BigUint::one();
 
-        for (j, &(x_j, _)) in shares.iter().enumerate() {
+        for (j, (x_j, _)) in shares.iter().enumerate() {
             if i != j {
-                let prime_big = BigUint::from(prime);
-                let x_i_big = BigUint::from(x_i);
-                let x_j_big = BigUint::from(x_j);
-
-                let numerator_big = BigUint::from(numerator);
-                let denominator_big = BigUint::from(denominator);
-
-                let new_numerator = (numerator_big * &x_j_big) % &prime_big;
-                let diff = if x_j_big >= x_i_big {
-                    x_j_big - x_i_big
-                } else {
-                    &prime_big - (x_i_big - x_j_big) % &prime_big
-                };
-                let new_denominator = (denominator_big * diff) % &prime_big;
-
-                numerator = new_numerator.to_u64().unwrap();
-                denominator = new_denominator.to_u64().unwrap();
+                numerator = (numerator * x_j) % prime;
+                // (x_j - x_i) mod prime, kept non-negative
+                let diff = (x_j + prime - x_i) % prime;
+                denominator = (denominator * diff) % prime;
             }
         }
 
         // Calculate modular inverse of denominator modulo prime
-        let inv_denominator = match modular_multiplicative_inverse(denominator, prime) {
-            Some(d) => d, // the "private exponent"
-            None => {
-                println!(
-                    "No modular multiplicative inverse exists for {} mod {}",
-                    denominator, prime
-                );
-                std::process::exit(1); // Exit with error code
-            }
-        };
+        let inv_denominator = modular_multiplicative_inverse(&denominator, prime)
+            .ok_or(ShamirError::NotInvertible(denominator))?;
 
         // Add current term to the secret
-        let term = (y_i * numerator * inv_denominator) % prime;
+        let term = (y_i * numerator % prime) * inv_denominator % prime;
         secret = (secret + term) % prime;
     }
 
-    // Ensure secret is positive
-    (secret + prime) % prime
+    Ok(secret)
 }
 
 /// Helper function to calculate modular multiplicative inverse.
-fn modular_multiplicative_inverse(e: u64, k: u64) -> Option<u64> {
-    let (gcd, x, _) = extended_gcd(e as i64, k as i64);
+fn modular_multiplicative_inverse(e: &BigUint, k: &BigUint) -> Option<BigUint> {
+    let k = k.to_bigint().unwrap();
+    let (gcd, x, _) = extended_gcd(&e.to_bigint().unwrap(), &k);
 
     // Check if the gcd is 1
-    if gcd != 1 {
+    if !gcd.is_one() {
         // No modular inverse if gcd is not 1
         return None;
     }
 
     // Ensure x is positive
-    let mut d = x % k as i64;
-    if d < 0 {
-        d += k as i64;
-    }
+    let d = ((x % &k) + &k) % &k;
 
-    Some(d as u64)
+    d.to_biguint()
 }
 
 /// Helper function to calculate GCD.
-fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
-    if a == 0 {
-        (b, 0, 1)
+fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    if a.is_zero() {
+        (b.clone(), BigInt::zero(), BigInt::one())
     } else {
-        let (gcd, x, y) = extended_gcd(b % a, a);
-        (gcd, y - (b / a) * x, x)
+        let (gcd, x, y) = extended_gcd(&(b % a), a);
+        (gcd, y - (b / a) * &x, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prime() -> BigUint {
+        prime_for_security_level(61)
+    }
+
+    #[test]
+    fn any_threshold_shares_reconstruct() {
+        let prime = prime();
+        let secret = BigUint::from(1559u32);
+        let shares = generate_secret_shares(&secret, 3, 5, &prime).unwrap();
+        assert_eq!(lagrange_interpolation(&shares[2..], 3, &prime), Ok(secret.clone()));
+        let reordered = [shares[4].clone(), shares[0].clone(), shares[2].clone()];
+        assert_eq!(lagrange_interpolation(&reordered, 3, &prime), Ok(secret));
+    }
+
+    #[test]
+    fn rejects_invalid_share_sets() {
+        let prime = prime();
+        let shares = generate_secret_shares(&BigUint::from(7u32), 3, 5, &prime).unwrap();
+        assert_eq!(
+            lagrange_interpolation(&shares[..2], 3, &prime),
+            Err(ShamirError::NotEnoughShares { have: 2, need: 3 })
+        );
+
+        let mut zero = shares.clone();
+        zero[1].0 = BigUint::zero();
+        assert_eq!(lagrange_interpolation(&zero, 3, &prime), Err(ShamirError::ZeroIndex));
+
+        let duplicated = [shares[0].clone(), shares[1].clone(), shares[0].clone()];
+        assert_eq!(
+            lagrange_interpolation(&duplicated, 3, &prime),
+            Err(ShamirError::DuplicateIndex(BigUint::one()))
+        );
+
+        let mut out_of_field = shares;
+        out_of_field[2].1 += &prime;
+        assert_eq!(
+            lagrange_interpolation(&out_of_field, 3, &prime),
+            Err(ShamirError::ValueOutOfRange(out_of_field[2].1.clone()))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_sharing_parameters() {
+        let prime = prime();
+        let secret = BigUint::from(7u32);
+        let ids = |id: u32| [BigUint::from(1001u32), BigUint::from(id)];
+        assert_eq!(
+            generate_secret_shares(&secret, 0, 5, &prime),
+            Err(ShamirError::InvalidThreshold { threshold: 0, nr_shares: 5 })
+        );
+        assert_eq!(
+            generate_secret_shares(&secret, 6, 5, &prime),
+            Err(ShamirError::InvalidThreshold { threshold: 6, nr_shares: 5 })
+        );
+        assert_eq!(
+            generate_secret_shares(&prime, 2, 5, &prime),
+            Err(ShamirError::SecretOutOfRange)
+        );
+        assert_eq!(
+            generate_secret_shares_at(&secret, 2, &ids(0), &prime),
+            Err(ShamirError::ZeroIndex)
+        );
+        assert_eq!(
+            generate_secret_shares_at(&secret, 2, &ids(1001), &prime),
+            Err(ShamirError::DuplicateIndex(BigUint::from(1001u32)))
+        );
+        let outside = [BigUint::from(1001u32), &prime + 1u32];
+        assert_eq!(
+            generate_secret_shares_at(&secret, 2, &outside, &prime),
+            Err(ShamirError::ValueOutOfRange(&prime + 1u32))
+        );
     }
 }
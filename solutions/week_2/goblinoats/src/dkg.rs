@@ -0,0 +1,492 @@
+//! Distributed key generation without a trusted dealer (Pedersen DKG with the GJKR fix).
+//!
+//! Every party deals a Pedersen VSS of a random z_i to all parties. Shares that fail verification
+//! are complained about, and a dealer who can't answer every complaint with a valid share in public,
+//! or who draws complaints from a threshold of parties, is disqualified. The qualified dealers
+//! form QUAL, the joint secret is x = sum of z_i over QUAL, and every party's share of it is the
+//! sum of the shares it received from QUAL.
+//!
+//! Only then does each qualified dealer publish g^{a_ik} for its coefficients, so the public key
+//! g^x = prod g^{z_i} can no longer be biased by choosing who gets disqualified. A dealer whose
+//! published values don't match a share that does match its Pedersen commitments is exposed,
+//! the other parties reveal their shares of its polynomial, and g^{z_i} is computed from the
+//! reconstructed z_i instead.
+//!
+//! The parties run in one process and exchange messages through in-memory queues, one round
+//! at a time, so a seeded run always plays out the same way.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use num_bigint::{BigInt, RandBigInt};
+use num_traits::{One, Zero};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::feldman::{evaluate_commitments, Group, VssError};
+use crate::pedersen::{
+    reconstruct_verified, second_generator, verify_share, Dealer, PedersenShare,
+};
+use crate::shamir::reconstruct_secret;
+
+/// How a simulated party deviates from the protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behaviour {
+    Honest,
+    /// Sends `victim` a bad share but reveals the right one when accused, so it stays qualified.
+    CorruptShare {
+        victim: usize,
+    },
+    /// Sends `victim` a bad share and ignores the complaint, which disqualifies it.
+    IgnoreComplaint {
+        victim: usize,
+    },
+    /// Publishes g^{z_i + 1} instead of g^{z_i} when the public key is extracted.
+    WrongPublicValue,
+}
+
+/// A protocol message. Parties are numbered from 0, and party i holds the share at x = i + 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Broadcast: the Pedersen commitments to the sender's polynomials.
+    Commitments(Vec<BigInt>),
+    /// Private: the recipient's share of the sender's polynomials.
+    Share(PedersenShare),
+    /// Broadcast: the share from `dealer` failed verification or never arrived.
+    Complaint { dealer: usize },
+    /// Broadcast: a dealer's answer to a complaint, the disputed share in the open.
+    Answer(PedersenShare),
+    /// Broadcast: g^{a_k} for every coefficient of the sender's secret polynomial.
+    PublicValues(Vec<BigInt>),
+    /// Broadcast: a share from `dealer` that matches its commitments but not its public values.
+    Accusation { dealer: usize, share: PedersenShare },
+    /// Broadcast: the sender's share of an exposed dealer's polynomials.
+    Reveal { dealer: usize, share: PedersenShare },
+}
+
+/// In-memory point-to-point queues, one inbox per party.
+///
+/// Messages sent during a round are held back until `deliver`, so a party never reads what the
+/// others sent in the round it is still taking part in.
+#[derive(Debug, Default)]
+pub struct Network {
+    inboxes: Vec<VecDeque<(usize, Message)>>,
+    in_flight: Vec<VecDeque<(usize, Message)>>,
+}
+
+impl Network {
+    /// Creates empty inboxes for `parties` parties.
+    pub fn new(parties: usize) -> Self {
+        Network {
+            inboxes: vec![VecDeque::new(); parties],
+            in_flight: vec![VecDeque::new(); parties],
+        }
+    }
+
+    /// Queues a message for one party.
+    pub fn send(&mut self, from: usize, to: usize, message: Message) {
+        self.in_flight[to].push_back((from, message));
+    }
+
+    /// Queues a message for every party, the sender included, in the same order for everyone.
+    pub fn broadcast(&mut self, from: usize, message: Message) {
+        for queue in &mut self.in_flight {
+            queue.push_back((from, message.clone()));
+        }
+    }
+
+    /// Ends the round: everything sent so far becomes readable.
+    pub fn deliver(&mut self) {
+        for (inbox, queue) in self.inboxes.iter_mut().zip(&mut self.in_flight) {
+            inbox.append(queue);
+        }
+    }
+
+    /// Takes everything delivered to a party so far, tagged with the sender.
+    pub fn receive(&mut self, party: usize) -> Vec<(usize, Message)> {
+        self.inboxes[party].drain(..).collect()
+    }
+}
+
+/// What a party ends up with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    pub x: BigInt,
+    /// The party's share of the joint secret key x.
+    pub share: BigInt,
+    /// g^x, which no party computed from x.
+    pub public_key: BigInt,
+    /// The dealers whose z_i make up the key.
+    pub qualified: Vec<usize>,
+}
+
+/// The x-coordinate of party `index`.
+fn x_of(index: usize) -> BigInt {
+    BigInt::from(index + 1)
+}
+
+/// One party's local view of the protocol.
+struct Party {
+    index: usize,
+    behaviour: Behaviour,
+    dealer: Dealer,
+    commitments: BTreeMap<usize, Vec<BigInt>>,
+    shares: BTreeMap<usize, PedersenShare>,
+    complaints: BTreeMap<usize, Vec<usize>>,
+    qualified: Vec<usize>,
+    public_values: BTreeMap<usize, Vec<BigInt>>,
+    exposed: BTreeSet<usize>,
+}
+
+impl Party {
+    /// Round 1: deal a VSS of a fresh random value.
+    fn deal(&self, group: &Group, parties: usize, network: &mut Network) {
+        network.broadcast(
+            self.index,
+            Message::Commitments(self.dealer.commitments.clone()),
+        );
+        for to in 0..parties {
+            let mut share = self.dealer.share_for(group, &x_of(to));
+            match self.behaviour {
+                Behaviour::CorruptShare { victim } | Behaviour::IgnoreComplaint { victim }
+                    if victim == to =>
+                {
+                    share.y = (share.y + BigInt::one()) % &group.q;
+                }
+                _ => {}
+            }
+            network.send(self.index, to, Message::Share(share));
+        }
+    }
+
+    /// Round 2: check the received shares and complain about the bad or missing ones.
+    fn verify(&mut self, group: &Group, h: &BigInt, parties: usize, network: &mut Network) {
+        for (from, message) in network.receive(self.index) {
+            match message {
+                Message::Commitments(commitments) => {
+                    self.commitments.insert(from, commitments);
+                }
+                Message::Share(share) => {
+                    self.shares.insert(from, share);
+                }
+                _ => {}
+            }
+        }
+        for dealer in 0..parties {
+            let valid = match (self.commitments.get(&dealer), self.shares.get(&dealer)) {
+                (Some(commitments), Some(share)) => {
+                    share.x == x_of(self.index) && verify_share(group, h, share, commitments)
+                }
+                _ => false,
+            };
+            if !valid {
+                network.broadcast(self.index, Message::Complaint { dealer });
+            }
+        }
+    }
+
+    /// Round 3: record the complaints and answer the ones against this party.
+    fn answer(&mut self, group: &Group, network: &mut Network) {
+        for (from, message) in network.receive(self.index) {
+            if let Message::Complaint { dealer } = message {
+                self.complaints.entry(dealer).or_default().push(from);
+                let silent = matches!(self.behaviour, Behaviour::IgnoreComplaint { .. });
+                if dealer == self.index && !silent {
+                    let share = self.dealer.share_for(group, &x_of(from));
+                    network.broadcast(self.index, Message::Answer(share));
+                }
+            }
+        }
+    }
+
+    /// Round 4: settle QUAL from the answers, then publish g^{a_k} for the own polynomial.
+    fn qualify(
+        &mut self,
+        group: &Group,
+        h: &BigInt,
+        threshold: usize,
+        parties: usize,
+        network: &mut Network,
+    ) {
+        let mut answers: BTreeMap<usize, Vec<PedersenShare>> = BTreeMap::new();
+        for (from, message) in network.receive(self.index) {
+            if let Message::Answer(share) = message {
+                answers.entry(from).or_default().push(share);
+            }
+        }
+
+        let mut disqualified = BTreeSet::new();
+        for dealer in 0..parties {
+            let Some(commitments) = self.commitments.get(&dealer) else {
+                disqualified.insert(dealer);
+                continue;
+            };
+            let complainants = self.complaints.get(&dealer).cloned().unwrap_or_default();
+            // Honest dealers can only draw complaints from the at most threshold - 1 cheaters
+            if commitments.len() != threshold || complainants.len() >= threshold {
+                disqualified.insert(dealer);
+                continue;
+            }
+            for complainant in complainants {
+                let revealed = answers.get(&dealer).and_then(|shares| {
+                    shares.iter().find(|share| {
+                        share.x == x_of(complainant) && verify_share(group, h, share, commitments)
+                    })
+                });
+                match revealed {
+                    Some(share) if complainant == self.index => {
+                        self.shares.insert(dealer, share.clone());
+                    }
+                    Some(_) => {}
+                    None => {
+                        disqualified.insert(dealer);
+                    }
+                }
+            }
+        }
+        self.qualified = (0..parties).filter(|d| !disqualified.contains(d)).collect();
+
+        if self.qualified.contains(&self.index) {
+            let mut values = self.dealer.feldman_commitments(group);
+            if self.behaviour == Behaviour::WrongPublicValue {
+                values[0] = (&values[0] * &group.g) % &group.p;
+            }
+            network.broadcast(self.index, Message::PublicValues(values));
+        }
+    }
+
+    /// Whether a share from `dealer` matches the dealer's published g^{a_k}.
+    fn matches_public_values(&self, group: &Group, dealer: usize, share: &PedersenShare) -> bool {
+        self.public_values.get(&dealer).is_some_and(|values| {
+            group.pow(&group.g, &share.y) == evaluate_commitments(group, values, &share.x)
+        })
+    }
+
+    /// Round 5: check every qualified dealer's public values against the own share.
+    fn check_public_values(&mut self, group: &Group, network: &mut Network) {
+        for (from, message) in network.receive(self.index) {
+            if let Message::PublicValues(values) = message {
+                self.public_values.insert(from, values);
+            }
+        }
+        for &dealer in &self.qualified {
+            let share = &self.shares[&dealer];
+            if !self.matches_public_values(group, dealer, share) {
+                let share = share.clone();
+                network.broadcast(self.index, Message::Accusation { dealer, share });
+            }
+        }
+    }
+
+    /// Round 6: expose dealers with a valid accusation and reveal the shares of their polynomial.
+    fn expose(&mut self, group: &Group, h: &BigInt, network: &mut Network) {
+        for (from, message) in network.receive(self.index) {
+            if let Message::Accusation { dealer, share } = message {
+                // The share must be the accuser's own and match the Pedersen commitments, so only
+                // the dealer can be at fault
+                let proven = self.qualified.contains(&dealer)
+                    && share.x == x_of(from)
+                    && verify_share(group, h, &share, &self.commitments[&dealer])
+                    && !self.matches_public_values(group, dealer, &share);
+                if proven {
+                    self.exposed.insert(dealer);
+                }
+            }
+        }
+        for &dealer in &self.exposed {
+            if dealer != self.index {
+                let share = self.shares[&dealer].clone();
+                network.broadcast(self.index, Message::Reveal { dealer, share });
+            }
+        }
+    }
+
+    /// Round 7: recover g^{z_i} of exposed dealers and combine the key.
+    fn finish(
+        &mut self,
+        group: &Group,
+        h: &BigInt,
+        network: &mut Network,
+    ) -> Result<KeyShare, VssError> {
+        let mut revealed: BTreeMap<usize, Vec<PedersenShare>> = BTreeMap::new();
+        for (_, message) in network.receive(self.index) {
+            if let Message::Reveal { dealer, share } = message {
+                revealed.entry(dealer).or_default().push(share);
+            }
+        }
+
+        let mut public_key = BigInt::one();
+        let mut share = BigInt::zero();
+        for &dealer in &self.qualified {
+            let value = if self.exposed.contains(&dealer) {
+                let shares = revealed.remove(&dealer).unwrap_or_default();
+                let z = reconstruct_verified(group, h, &shares, &self.commitments[&dealer])?;
+                group.pow(&group.g, &z)
+            } else {
+                self.public_values[&dealer][0].clone()
+            };
+            public_key = (public_key * value) % &group.p;
+            share = (share + &self.shares[&dealer].y) % &group.q;
+        }
+        Ok(KeyShare {
+            x: x_of(self.index),
+            share,
+            public_key,
+            qualified: self.qualified.clone(),
+        })
+    }
+}
+
+/// Runs the DKG among simulated parties.
+///
+/// # Arguments
+///
+/// * `group` - The group the key lives in; shares are taken modulo `group.q`.
+/// * `h` - The second generator from `second_generator`.
+/// * `threshold` - The number of shares needed to use the key. Up to threshold - 1 parties may
+///   cheat.
+/// * `behaviours` - One entry per party.
+/// * `rng` - The randomness of all parties, seed it for a repeatable run.
+///
+/// # Returns
+///
+/// Every party's key share in party order, or an error if an exposed dealer's value could not be
+/// reconstructed because too few parties revealed valid shares.
+pub fn run_dkg<R: Rng + ?Sized>(
+    group: &Group,
+    h: &BigInt,
+    threshold: usize,
+    behaviours: &[Behaviour],
+    rng: &mut R,
+) -> Result<Vec<KeyShare>, VssError> {
+    let parties = behaviours.len();
+    assert!(
+        threshold >= 1 && threshold <= parties,
+        "threshold must be between 1 and the number of parties"
+    );
+    let mut network = Network::new(parties);
+    let mut states: Vec<Party> = behaviours
+        .iter()
+        .enumerate()
+        .map(|(index, &behaviour)| {
+            let z = rng.gen_bigint_range(&BigInt::zero(), &group.q);
+            Party {
+                index,
+                behaviour,
+                dealer: Dealer::with_rng(group, h, threshold, &z, rng),
+                commitments: BTreeMap::new(),
+                shares: BTreeMap::new(),
+                complaints: BTreeMap::new(),
+                qualified: Vec::new(),
+                public_values: BTreeMap::new(),
+                exposed: BTreeSet::new(),
+            }
+        })
+        .collect();
+
+    // Each round only reads what was sent in the previous one
+    for party in &states {
+        party.deal(group, parties, &mut network);
+    }
+    network.deliver();
+    for party in &mut states {
+        party.verify(group, h, parties, &mut network);
+    }
+    network.deliver();
+    for party in &mut states {
+        party.answer(group, &mut network);
+    }
+    network.deliver();
+    for party in &mut states {
+        party.qualify(group, h, threshold, parties, &mut network);
+    }
+    network.deliver();
+    for party in &mut states {
+        party.check_public_values(group, &mut network);
+    }
+    network.deliver();
+    for party in &mut states {
+        party.expose(group, h, &mut network);
+    }
+    network.deliver();
+    states
+        .iter_mut()
+        .map(|party| party.finish(group, h, &mut network))
+        .collect()
+}
+
+/// Seven parties generate a 4-of-7 key while three of them misbehave in different ways.
+pub fn run_demo() {
+    let group = Group::modp_2048();
+    let h = second_generator(&group);
+    let behaviours = [
+        Behaviour::Honest,
+        Behaviour::CorruptShare { victim: 0 },
+        Behaviour::IgnoreComplaint { victim: 4 },
+        Behaviour::WrongPublicValue,
+        Behaviour::Honest,
+        Behaviour::Honest,
+        Behaviour::Honest,
+    ];
+    let mut rng = StdRng::seed_from_u64(2024);
+    let keys = run_dkg(&group, &h, 4, &behaviours, &mut rng).unwrap();
+
+    for (index, (key, behaviour)) in keys.iter().zip(&behaviours).enumerate() {
+        println!(
+            "Party {} ({:?}): QUAL {:?}, public key {}...",
+            index,
+            behaviour,
+            key.qualified,
+            &key.public_key.to_string()[..12]
+        );
+    }
+
+    // Nobody ever held x, but any 4 parties can recover it and it matches the public key
+    let points: Vec<(BigInt, BigInt)> = [0, 1, 5, 6]
+        .iter()
+        .map(|&i| (keys[i].x.clone(), keys[i].share.clone()))
+        .collect();
+    let secret = reconstruct_secret(&points, &group.q).unwrap();
+    println!(
+        "Parties 0, 1, 5 and 6 recover a key with g^x = public key: {}",
+        group.pow(&group.g, &secret) == keys[0].public_key
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parties_agree_on_the_key_despite_cheaters() {
+        // A 64-bit safe prime keeps the many exponentiations fast, the protocol is the same
+        let group = Group {
+            p: BigInt::from(18446744073709550147u64),
+            q: BigInt::from(9223372036854775073u64),
+            g: BigInt::from(4),
+        };
+        let h = second_generator(&group);
+        let behaviours = [
+            Behaviour::CorruptShare { victim: 2 },
+            Behaviour::Honest,
+            Behaviour::IgnoreComplaint { victim: 1 },
+            Behaviour::Honest,
+            Behaviour::WrongPublicValue,
+        ];
+        let keys = run_dkg(&group, &h, 3, &behaviours, &mut StdRng::seed_from_u64(7)).unwrap();
+
+        // Same seed, same run
+        let again = run_dkg(&group, &h, 3, &behaviours, &mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(keys, again);
+
+        for key in &keys {
+            assert_eq!(key.qualified, vec![0, 1, 3, 4]);
+            assert_eq!(key.public_key, keys[0].public_key);
+        }
+        let points: Vec<(BigInt, BigInt)> = keys[1..4]
+            .iter()
+            .map(|key| (key.x.clone(), key.share.clone()))
+            .collect();
+        let secret = reconstruct_secret(&points, &group.q).unwrap();
+        assert_eq!(group.pow(&group.g, &secret), keys[0].public_key);
+    }
+}
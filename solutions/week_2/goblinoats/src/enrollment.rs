@@ -0,0 +1,182 @@
+//! Share enrollment and repair without reconstructing the secret.
+//!
+//! Any t holders can produce the share f(x_new) for a new or returning participant, since
+//! f(x_new) = sum L_i(x_new) y_i over the helpers' shares. Sending L_i(x_new) y_i directly would
+//! let the new participant solve for every y_i, so each helper first splits its weighted share
+//! into t random additive pieces, one per helper. Every helper only forwards the sum of the pieces
+//! it received, which is uniformly random on its own, and the sum of those sums is f(x_new).
+
+use num_bigint::{BigInt, RandBigInt, Sign};
+use num_traits::Zero;
+use rand::thread_rng;
+
+use crate::shamir::{
+    check_shares, create_shares, interpolate_at, lagrange_coefficients_at, modp_2048_prime,
+    reconstruct_secret, ShamirError,
+};
+
+/// Step 1 for one helper: weights its share with its Lagrange coefficient at `x_new` and splits
+/// the result into one random additive piece per helper.
+///
+/// # Arguments
+///
+/// * `share` - This helper's share.
+/// * `helper_xs` - The x-coordinates of all t helpers, including this one.
+/// * `x_new` - The x-coordinate of the share being enrolled.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The pieces, where piece j goes to the helper at `helper_xs[j]`.
+pub fn mask_contribution(
+    share: &(BigInt, BigInt),
+    helper_xs: &[BigInt],
+    x_new: &BigInt,
+    prime: &BigInt,
+) -> Result<Vec<BigInt>, ShamirError> {
+    let weights = lagrange_coefficients_at(helper_xs, x_new, prime)?;
+    // A share that is not among the helpers has no coefficient
+    let index = helper_xs
+        .iter()
+        .position(|x| *x == share.0)
+        .ok_or_else(|| ShamirError::UnknownHelper(share.0.clone()))?;
+    let contribution = (&weights[index] * &share.1) % prime;
+
+    // All pieces but the last are uniform; the last one makes them sum to the contribution
+    let mut rng = thread_rng();
+    let mut pieces: Vec<BigInt> = (1..helper_xs.len())
+        .map(|_| rng.gen_bigint_range(&BigInt::zero(), prime))
+        .collect();
+    let masked_sum = pieces
+        .iter()
+        .fold(BigInt::zero(), |acc, piece| (acc + piece) % prime);
+    pieces.push(((contribution - masked_sum) % prime + prime) % prime);
+    Ok(pieces)
+}
+
+/// Step 2 for every helper, and step 3 for the new participant: adds up received values.
+pub fn sum_pieces(received: &[BigInt], prime: &BigInt) -> BigInt {
+    received
+        .iter()
+        .fold(BigInt::zero(), |acc, piece| (acc + piece) % prime)
+}
+
+/// Runs the whole enrollment protocol among the helpers in memory.
+///
+/// # Arguments
+///
+/// * `helpers` - Exactly threshold many shares of the existing holders taking part.
+/// * `x_new` - The x-coordinate of the new participant, non-zero and not a helper's.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The new share (x_new, f(x_new)), or an error if the helpers' shares or `x_new` are invalid.
+pub fn enroll(
+    helpers: &[(BigInt, BigInt)],
+    x_new: &BigInt,
+    prime: &BigInt,
+) -> Result<(BigInt, BigInt), ShamirError> {
+    check_shares(helpers, prime)?;
+    if x_new.is_zero() {
+        return Err(ShamirError::ZeroIndex);
+    }
+    if x_new.sign() == Sign::Minus || x_new >= prime {
+        return Err(ShamirError::ValueOutOfRange(x_new.clone()));
+    }
+    if helpers.iter().any(|(x, _)| x == x_new) {
+        return Err(ShamirError::DuplicateIndex(x_new.clone()));
+    }
+
+    // sent[i][j] is the piece helper i sends to helper j
+    let helper_xs: Vec<BigInt> = helpers.iter().map(|(x, _)| x.clone()).collect();
+    let sent = helpers
+        .iter()
+        .map(|share| mask_contribution(share, &helper_xs, x_new, prime))
+        .collect::<Result<Vec<_>, _>>()?;
+    let forwarded: Vec<BigInt> = (0..helpers.len())
+        .map(|j| {
+            let received: Vec<BigInt> = sent.iter().map(|pieces| pieces[j].clone()).collect();
+            sum_pieces(&received, prime)
+        })
+        .collect();
+    Ok((x_new.clone(), sum_pieces(&forwarded, prime)))
+}
+
+/// Repairs a lost share of a 3-of-5 sharing and enrolls a sixth participant.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let secret = BigInt::from(123456789);
+    let shares = create_shares(5, 3, &secret, &prime);
+
+    // Holder 4 lost its share; holders 1, 3 and 5 rebuild it
+    let helpers = [shares[0].clone(), shares[2].clone(), shares[4].clone()];
+    let repaired = enroll(&helpers, &BigInt::from(4), &prime).unwrap();
+    println!(
+        "Repaired share 4 matches the lost one: {}",
+        repaired == shares[3]
+    );
+
+    let newcomer = enroll(&helpers, &BigInt::from(6), &prime).unwrap();
+    println!(
+        "Share 6 matches the polynomial: {}",
+        Ok(newcomer.1.clone()) == interpolate_at(&shares[..3], &BigInt::from(6), &prime)
+    );
+    let combined = [shares[1].clone(), repaired, newcomer];
+    println!(
+        "Shares 2, 4 (repaired) and 6 (new) give: {}",
+        reconstruct_secret(&combined, &prime).unwrap()
+    );
+
+    if let Err(error) = enroll(&helpers, &BigInt::from(3), &prime) {
+        println!("Enrolling x = 3 again: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enrolled_share_lies_on_the_polynomial() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let secret = BigInt::from(4242);
+        let shares = create_shares(6, 4, &secret, &prime);
+
+        let repaired = enroll(&shares[2..], &BigInt::from(1), &prime).unwrap();
+        assert_eq!(repaired, shares[0]);
+        let x_new = BigInt::from(1000);
+        let enrolled = enroll(&shares[..4], &x_new, &prime).unwrap();
+        assert_eq!(
+            Ok(enrolled.1.clone()),
+            interpolate_at(&shares[1..5], &x_new, &prime)
+        );
+        assert_eq!(
+            interpolate_at(&shares[..4], &BigInt::zero(), &prime),
+            Ok(secret.clone())
+        );
+
+        // Each helper's pieces are random but add up to its weighted share
+        let xs: Vec<BigInt> = shares[..4].iter().map(|(x, _)| x.clone()).collect();
+        let pieces = mask_contribution(&shares[0], &xs, &x_new, &prime).unwrap();
+        assert_eq!(pieces.len(), 4);
+        let weight = &lagrange_coefficients_at(&xs, &x_new, &prime).unwrap()[0];
+        assert_eq!(
+            sum_pieces(&pieces, &prime),
+            (weight * &shares[0].1) % &prime
+        );
+        assert_eq!(
+            mask_contribution(&shares[5], &xs, &x_new, &prime),
+            Err(ShamirError::UnknownHelper(BigInt::from(6)))
+        );
+
+        assert_eq!(
+            enroll(&shares[..4], &BigInt::from(2), &prime),
+            Err(ShamirError::DuplicateIndex(BigInt::from(2)))
+        );
+        assert_eq!(
+            enroll(&shares[..4], &BigInt::zero(), &prime),
+            Err(ShamirError::ZeroIndex)
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Feldman verifiable secret sharing.
+//!
+//! Shares live in Z_q and the dealer publishes C_i = g^{a_i} mod p for every coefficient, where g
+//! generates the subgroup of prime order q = (p - 1) / 2 of the 2048-bit MODP group. A holder of
+//! (x, y) checks g^y == prod C_i^{x^i}, which holds exactly when y = f(x) for the committed f.
+
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+
+use crate::shamir::{
+    evaluate_polynomial, generate_coefficients, modp_2048_prime, reconstruct_secret, ShamirError,
+};
+
+/// The prime-order subgroup the commitments live in.
+#[derive(Clone, Debug)]
+pub struct Group {
+    /// The safe prime p = 2q + 1.
+    pub p: BigInt,
+    /// The order of the subgroup, which is also the field the shares live in.
+    pub q: BigInt,
+    /// A generator of the subgroup of quadratic residues.
+    pub g: BigInt,
+}
+
+impl Group {
+    /// The quadratic residues modulo the 2048-bit MODP prime, generated by 4 = 2^2.
+    pub fn modp_2048() -> Self {
+        let p = modp_2048_prime();
+        let q = (&p - BigInt::one()) >> 1;
+        Group {
+            p,
+            q,
+            g: BigInt::from(4),
+        }
+    }
+
+    /// Computes base^exponent mod p.
+    pub fn pow(&self, base: &BigInt, exponent: &BigInt) -> BigInt {
+        base.modpow(exponent, &self.p)
+    }
+
+    /// Whether the value is a reduced exponent, 0 <= value < q. `pow` panics on negative ones,
+    /// so untrusted share values are checked with this first.
+    pub fn is_exponent(&self, value: &BigInt) -> bool {
+        value.sign() != Sign::Minus && value < &self.q
+    }
+}
+
+/// Why verified reconstruction failed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum VssError {
+    /// Fewer than `threshold` shares passed verification. `rejected` lists the failing x's.
+    NotEnoughValidShares {
+        valid: usize,
+        threshold: usize,
+        rejected: Vec<BigInt>,
+    },
+    /// The verified shares could still not be interpolated, e.g. two of them share an x.
+    Reconstruction(ShamirError),
+}
+
+impl fmt::Display for VssError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VssError::NotEnoughValidShares {
+                valid,
+                threshold,
+                rejected,
+            } => write!(
+                f,
+                "only {} of the needed {} shares verified, rejected shares at x = {:?}",
+                valid, threshold, rejected
+            ),
+            VssError::Reconstruction(error) => write!(f, "reconstruction failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for VssError {}
+
+/// Evaluates prod commitments[i]^{x^i} mod p, the commitment to f(x).
+///
+/// Feldman and Pedersen commitments are both checked this way, since both are homomorphic in the
+/// committed polynomial.
+pub fn evaluate_commitments(group: &Group, commitments: &[BigInt], x: &BigInt) -> BigInt {
+    let mut result = BigInt::one();
+    let mut x_pow = BigInt::one();
+    for commitment in commitments {
+        result = (result * group.pow(commitment, &x_pow)) % &group.p;
+        x_pow = (x_pow * x) % &group.q;
+    }
+    result
+}
+
+/// Splits the secret into verifiable shares.
+///
+/// # Arguments
+///
+/// * `group` - The group to commit in; shares are taken modulo `group.q`.
+/// * `num_shares` - The total number of shares to create.
+/// * `threshold` - The minimum number of shares required to reconstruct the secret.
+/// * `secret` - The secret to be shared, smaller than `group.q`.
+///
+/// # Returns
+///
+/// The shares as (x, y) tuples and the commitments g^{a_i} to each coefficient.
+pub fn create_shares(
+    group: &Group,
+    num_shares: usize,
+    threshold: usize,
+    secret: &BigInt,
+) -> (Vec<(BigInt, BigInt)>, Vec<BigInt>) {
+    assert!(
+        *secret >= BigInt::zero() && *secret < group.q,
+        "secret must be in [0, q)"
+    );
+    let coefficients = generate_coefficients(threshold, secret, &group.q);
+    let commitments = coefficients
+        .iter()
+        .map(|coefficient| group.pow(&group.g, coefficient))
+        .collect();
+    let shares = (1..=num_shares)
+        .map(|i| {
+            let x = BigInt::from(i);
+            let y = evaluate_polynomial(&x, &coefficients, &group.q);
+            (x, y)
+        })
+        .collect();
+    (shares, commitments)
+}
+
+/// Checks a share against the dealer's commitments.
+///
+/// # Arguments
+///
+/// * `group` - The group the commitments live in.
+/// * `share` - The (x, y) share to check.
+/// * `commitments` - The dealer's published commitments.
+///
+/// # Returns
+///
+/// `true` if x and y are in range and g^y equals the commitment to f(x).
+pub fn verify_share(group: &Group, share: &(BigInt, BigInt), commitments: &[BigInt]) -> bool {
+    let (x, y) = share;
+    if x.is_zero() || !group.is_exponent(x) || !group.is_exponent(y) {
+        return false;
+    }
+    group.pow(&group.g, y) == evaluate_commitments(group, commitments, x)
+}
+
+/// Reconstructs the secret from the shares that verify against the commitments.
+///
+/// # Arguments
+///
+/// * `group` - The group the commitments live in.
+/// * `shares` - The shares to reconstruct from; failing shares are dropped.
+/// * `commitments` - The dealer's published commitments, one per coefficient.
+///
+/// # Returns
+///
+/// The secret, or an error listing the rejected shares if fewer than `threshold` distinct shares
+/// verified.
+pub fn reconstruct_verified(
+    group: &Group,
+    shares: &[(BigInt, BigInt)],
+    commitments: &[BigInt],
+) -> Result<BigInt, VssError> {
+    let threshold = commitments.len();
+    let (verified, rejected): (Vec<_>, Vec<_>) = shares
+        .iter()
+        .cloned()
+        .partition(|share| verify_share(group, share, commitments));
+    // A verified share is f(x), so a repeated x adds nothing and would break interpolation
+    let mut valid: Vec<(BigInt, BigInt)> = Vec::with_capacity(verified.len());
+    for share in verified {
+        if !valid.iter().any(|(x, _)| *x == share.0) {
+            valid.push(share);
+        }
+    }
+    if valid.len() < threshold {
+        return Err(VssError::NotEnoughValidShares {
+            valid: valid.len(),
+            threshold,
+            rejected: rejected.into_iter().map(|(x, _)| x).collect(),
+        });
+    }
+    // Any threshold of the valid shares determine the same polynomial
+    reconstruct_secret(&valid[..threshold], &group.q).map_err(VssError::Reconstruction)
+}
+
+/// Runs a dealing where the dealer cheats on one share.
+pub fn run_demo() {
+    let group = Group::modp_2048();
+    let secret = BigInt::from(123456789);
+    let (mut shares, commitments) = create_shares(&group, 5, 3, &secret);
+    println!("Dealer published {} commitments", commitments.len());
+
+    // A dishonest dealer hands the third holder a share off the committed polynomial
+    shares[2].1 = (&shares[2].1 + BigInt::one()) % &group.q;
+    for share in &shares {
+        println!(
+            "Share at x = {} verifies: {}",
+            share.0,
+            verify_share(&group, share, &commitments)
+        );
+    }
+
+    match reconstruct_verified(&group, &shares, &commitments) {
+        Ok(recovered) => println!("Recovered secret from verified shares: {}", recovered),
+        Err(error) => println!("Reconstruction refused: {}", error),
+    }
+    match reconstruct_verified(&group, &shares[1..4], &commitments) {
+        Ok(recovered) => println!("Recovered secret from shares 2-4: {}", recovered),
+        Err(error) => println!("Reconstruction from shares 2-4 refused: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_inconsistent_shares() {
+        let group = Group::modp_2048();
+        let secret = BigInt::from(42);
+        let (mut shares, commitments) = create_shares(&group, 4, 2, &secret);
+        assert!(shares.iter().all(|share| verify_share(&group, share, &commitments)));
+
+        shares[0].1 += 1;
+        shares[3].1 += 1;
+        assert!(!verify_share(&group, &shares[0], &commitments));
+        // Out-of-range values are rejected instead of reaching modpow
+        assert!(!verify_share(&group, &(BigInt::from(2), BigInt::from(-1)), &commitments));
+        assert!(!verify_share(&group, &(BigInt::from(-2), shares[1].1.clone()), &commitments));
+        assert_eq!(reconstruct_verified(&group, &shares, &commitments), Ok(secret.clone()));
+        assert_eq!(
+            reconstruct_verified(&group, &[shares[0].clone(), shares[1].clone()], &commitments),
+            Err(VssError::NotEnoughValidShares {
+                valid: 1,
+                threshold: 2,
+                rejected: vec![BigInt::from(1)],
+            })
+        );
+        // A repeated valid share counts once
+        assert_eq!(
+            reconstruct_verified(&group, &[shares[1].clone(), shares[1].clone()], &commitments),
+            Err(VssError::NotEnoughValidShares { valid: 1, threshold: 2, rejected: vec![] })
+        );
+        let repeated = [shares[1].clone(), shares[1].clone(), shares[2].clone()];
+        assert_eq!(reconstruct_verified(&group, &repeated, &commitments), Ok(secret));
+    }
+}
@@ -0,0 +1,245 @@
+//! Tassa's hierarchical threshold sharing with Birkhoff interpolation.
+//!
+//! Participants sit in levels 0 (most senior) to m - 1 with increasing thresholds
+//! k_0 < k_1 < ... < k_{m-1} = k. A set is authorized if for every level j it contains at least k_j
+//! participants from levels 0..=j, e.g. "any 3 people, at least 1 of them an officer" is k = (1, 3).
+//! The dealer picks f of degree k - 1 with f(0) = secret, and a participant at level j > 0 with ID x
+//! gets the derivative f^(k_{j-1})(x) instead of f(x). Derivatives of order k_{j-1} lose the
+//! coefficients below that degree, so junior shares can't help recover a_0 without enough senior
+//! ones. Reconstruction solves the linear system of the shares for the coefficients (Birkhoff
+//! interpolation). Tassa shows the system is non-singular with overwhelming probability for
+//! authorized sets if IDs increase with the level, so seniors should get the smallest IDs.
+
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+
+use crate::poly::{derivative, evaluate};
+use crate::robust::solve_unique;
+use crate::shamir::{check_shares, generate_coefficients, modp_2048_prime, ShamirError};
+
+/// A share of a participant at a level of the hierarchy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HierarchicalShare {
+    pub level: usize,
+    pub x: BigInt,
+    pub y: BigInt,
+}
+
+/// A conjunctive hierarchical access structure over a prime field.
+#[derive(Clone, Debug)]
+pub struct HierarchicalScheme {
+    thresholds: Vec<usize>,
+    prime: BigInt,
+}
+
+impl HierarchicalScheme {
+    /// Creates the scheme for the given cumulative level thresholds.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - k_0 < k_1 < ... , where k_j is the number of participants needed from
+    ///   levels 0..=j. The last one is the overall threshold.
+    /// * `prime` - A large prime number for modulo operations.
+    ///
+    /// # Returns
+    ///
+    /// The scheme, or an error if there are no levels or the thresholds are not increasing.
+    pub fn new(thresholds: &[usize], prime: &BigInt) -> Result<Self, ShamirError> {
+        let increasing = thresholds.windows(2).all(|pair| pair[0] < pair[1]);
+        if thresholds.first().is_none_or(|&k| k == 0) || !increasing {
+            return Err(ShamirError::InvalidThreshold {
+                threshold: thresholds.first().copied().unwrap_or(0),
+                num_shares: thresholds.len(),
+            });
+        }
+        Ok(HierarchicalScheme {
+            thresholds: thresholds.to_vec(),
+            prime: prime.clone(),
+        })
+    }
+
+    /// The order of the derivative handed to participants at `level`.
+    fn derivative_order(&self, level: usize) -> usize {
+        if level == 0 {
+            0
+        } else {
+            self.thresholds[level - 1]
+        }
+    }
+
+    /// Deals one share per participant.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret to be shared.
+    /// * `participants` - The level and ID of every participant; IDs should increase with the
+    ///   level.
+    ///
+    /// # Returns
+    ///
+    /// The shares in participant order, or an error if a level does not exist or an ID is zero,
+    /// repeated or outside the field.
+    pub fn share(
+        &self,
+        secret: &BigInt,
+        participants: &[(usize, BigInt)],
+    ) -> Result<Vec<HierarchicalShare>, ShamirError> {
+        self.check_participants(participants.iter().map(|(level, x)| (*level, x)))?;
+        let k = *self.thresholds.last().unwrap();
+        let coefficients = generate_coefficients(k, secret, &self.prime);
+
+        // derivatives[d] = f^(d), only for the orders that are actually handed out
+        let mut derivatives = vec![coefficients];
+        while derivatives.len() <= self.derivative_order(self.thresholds.len() - 1) {
+            let next = derivative(derivatives.last().unwrap(), &self.prime);
+            derivatives.push(next);
+        }
+
+        Ok(participants
+            .iter()
+            .map(|(level, x)| HierarchicalShare {
+                level: *level,
+                x: x.clone(),
+                y: evaluate(&derivatives[self.derivative_order(*level)], x, &self.prime),
+            })
+            .collect())
+    }
+
+    /// Rejects unknown levels and invalid IDs.
+    fn check_participants<'a>(
+        &self,
+        participants: impl Iterator<Item = (usize, &'a BigInt)>,
+    ) -> Result<(), ShamirError> {
+        let mut points = Vec::new();
+        for (level, x) in participants {
+            if level >= self.thresholds.len() {
+                return Err(ShamirError::ParameterMismatch {
+                    expected: self.thresholds.len(),
+                    found: level + 1,
+                });
+            }
+            points.push((x.clone(), BigInt::zero()));
+        }
+        check_shares(&points, &self.prime)
+    }
+
+    /// Decides whether participants at the given levels form an authorized set: for every level j
+    /// at least k_j of them are at level j or above.
+    pub fn is_authorized(&self, levels: &[usize]) -> bool {
+        if levels.iter().any(|&level| level >= self.thresholds.len()) {
+            return false;
+        }
+        self.thresholds
+            .iter()
+            .enumerate()
+            .all(|(j, &k)| levels.iter().filter(|&&level| level <= j).count() >= k)
+    }
+
+    /// Reconstructs the secret by Birkhoff interpolation.
+    ///
+    /// # Arguments
+    ///
+    /// * `shares` - The shares of the participating holders.
+    ///
+    /// # Returns
+    ///
+    /// The secret, `NotAuthorized` before any interpolation if the set is not authorized, or
+    /// `SingularSystem` if the shares do not pin down the polynomial.
+    pub fn reconstruct(&self, shares: &[HierarchicalShare]) -> Result<BigInt, ShamirError> {
+        self.check_participants(shares.iter().map(|share| (share.level, &share.x)))?;
+        let levels: Vec<usize> = shares.iter().map(|share| share.level).collect();
+        if !self.is_authorized(&levels) {
+            return Err(ShamirError::NotAuthorized);
+        }
+
+        // Row for f^(d)(x): the d-th derivative of X^c is c! / (c - d)! X^(c - d)
+        let k = *self.thresholds.last().unwrap();
+        let matrix: Vec<Vec<BigInt>> = shares
+            .iter()
+            .map(|share| {
+                let d = self.derivative_order(share.level);
+                let mut row = vec![BigInt::zero(); k];
+                let mut power = BigInt::one();
+                for (c, entry) in row.iter_mut().enumerate().skip(d) {
+                    let falling = ((c - d + 1)..=c).fold(BigInt::one(), |acc, i| acc * i);
+                    *entry = (falling * &power) % &self.prime;
+                    power = (power * &share.x) % &self.prime;
+                }
+                row
+            })
+            .collect();
+        let rhs: Vec<BigInt> = shares.iter().map(|share| share.y.clone()).collect();
+        let coefficients =
+            solve_unique(matrix, rhs, &self.prime).ok_or(ShamirError::SingularSystem)?;
+        Ok(coefficients[0].clone())
+    }
+}
+
+/// "Any 3 people, at least 1 of them an officer" with 2 officers and 4 staff.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let secret = BigInt::from(123456789);
+    let scheme = HierarchicalScheme::new(&[1, 3], &prime).unwrap();
+    // Officers get the smallest IDs
+    let participants: Vec<(usize, BigInt)> = [(0, 1), (0, 2), (1, 3), (1, 4), (1, 5), (1, 6)]
+        .into_iter()
+        .map(|(level, x)| (level, BigInt::from(x)))
+        .collect();
+    let shares = scheme.share(&secret, &participants).unwrap();
+    let names = [
+        "officer A",
+        "officer B",
+        "staff 1",
+        "staff 2",
+        "staff 3",
+        "staff 4",
+    ];
+
+    for group in [vec![0, 2, 3], vec![0, 1, 5], vec![2, 3, 4, 5], vec![0, 1]] {
+        let selected: Vec<HierarchicalShare> = group.iter().map(|&i| shares[i].clone()).collect();
+        let members: Vec<&str> = group.iter().map(|&i| names[i]).collect();
+        match scheme.reconstruct(&selected) {
+            Ok(value) => println!("{}: recovered {}", members.join(", "), value),
+            Err(error) => println!("{}: {}", members.join(", "), error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn birkhoff_interpolation_respects_the_hierarchy() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let secret = BigInt::from(2024);
+        let scheme = HierarchicalScheme::new(&[2, 4, 5], &prime).unwrap();
+        let participants: Vec<(usize, BigInt)> = [0, 0, 0, 1, 1, 2, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(i, level)| (level, BigInt::from(i + 1)))
+            .collect();
+        let shares = scheme.share(&secret, &participants).unwrap();
+
+        let pick = |indices: &[usize]| -> Vec<HierarchicalShare> {
+            indices.iter().map(|&i| shares[i].clone()).collect()
+        };
+        assert_eq!(
+            scheme.reconstruct(&pick(&[0, 1, 3, 4, 5])),
+            Ok(secret.clone())
+        );
+        assert_eq!(
+            scheme.reconstruct(&pick(&[0, 1, 2, 3, 6])),
+            Ok(secret.clone())
+        );
+        assert_eq!(scheme.reconstruct(&shares), Ok(secret));
+
+        // One senior share is not enough even with everyone else
+        assert!(!scheme.is_authorized(&[0, 1, 1, 2, 2]));
+        assert_eq!(
+            scheme.reconstruct(&pick(&[0, 3, 4, 5, 6])),
+            Err(ShamirError::NotAuthorized)
+        );
+        assert!(HierarchicalScheme::new(&[2, 2], &prime).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Sharing large files by encrypting them and sharing only the key.
+//!
+//! A share from `share::split` is as long as the secret, so n holders of a 100 MB file would
+//! store 100 MB each. Instead the file is encrypted once under a fresh AES-256-GCM key, and only
+//! the 32-byte key is shared with `gf256`. The ciphertext can be stored once where every holder
+//! can reach it, or be spread with `ida` so that each holder keeps |file| / t bytes of it. The
+//! GCM tag makes decryption fail if the ciphertext or the recovered key is wrong.
+
+use std::fmt;
+
+use aes_gcm::aead::{Aead, OsRng};
+use aes_gcm::{AeadCore, Aes256Gcm, KeyInit};
+
+use crate::ida::{self, Fragment};
+use crate::shamir::ShamirError;
+use crate::share::{self, CombineError, Scheme, Share};
+
+/// Length of the AES-256 key that gets shared.
+pub const KEY_LEN: usize = 32;
+
+/// AES-GCM uses a 12-byte nonce, stored in front of the ciphertext.
+const NONCE_LEN: usize = 12;
+
+/// The key shares and the ciphertext of a split file.
+pub struct SplitFile {
+    pub key_shares: Vec<Share>,
+    /// nonce || AES-256-GCM ciphertext and tag.
+    pub ciphertext: Vec<u8>,
+}
+
+/// Why a file could not be put back together.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FileError {
+    /// The key shares did not combine.
+    Key(CombineError),
+    /// The ciphertext fragments did not combine.
+    Fragments(ShamirError),
+    /// The ciphertext is damaged or does not belong to the key.
+    Decryption,
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileError::Key(error) => write!(f, "key shares: {}", error),
+            FileError::Fragments(error) => write!(f, "ciphertext fragments: {}", error),
+            FileError::Decryption => {
+                write!(
+                    f,
+                    "decryption failed, the ciphertext does not match the key"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+/// Encrypts the data under the key with a random nonce.
+///
+/// # Returns
+///
+/// The nonce followed by the ciphertext and tag.
+pub fn encrypt_aes(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Vec<u8> {
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .expect("AES-GCM encryption can't fail for in-memory data");
+    [nonce.as_slice(), &ciphertext].concat()
+}
+
+/// Decrypts the output of `encrypt_aes`.
+///
+/// # Returns
+///
+/// The plaintext, or `FileError::Decryption` if the key is wrong or the data was modified.
+pub fn decrypt_aes(key: &[u8], data: &[u8]) -> Result<Vec<u8>, FileError> {
+    if key.len() != KEY_LEN || data.len() < NONCE_LEN {
+        return Err(FileError::Decryption);
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| FileError::Decryption)?;
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    cipher
+        .decrypt(nonce.into(), ciphertext)
+        .map_err(|_| FileError::Decryption)
+}
+
+/// Encrypts a file under a fresh key and splits the key.
+///
+/// # Arguments
+///
+/// * `contents` - The file contents, of any length.
+/// * `threshold` - The minimum number of key shares required to decrypt.
+/// * `num_shares` - The total number of key shares to create, at most 255.
+///
+/// # Returns
+///
+/// The key shares and the ciphertext, which is only 28 bytes longer than the file.
+pub fn split_file(contents: &[u8], threshold: usize, num_shares: usize) -> SplitFile {
+    let mut key: [u8; KEY_LEN] = Aes256Gcm::generate_key(&mut OsRng).into();
+    let ciphertext = encrypt_aes(&key, contents);
+    let key_shares = share::split(&key, Scheme::Gf256, threshold, num_shares);
+    key.iter_mut().for_each(|byte| *byte = 0);
+    SplitFile {
+        key_shares,
+        ciphertext,
+    }
+}
+
+/// Combines the key shares and decrypts the file.
+///
+/// # Arguments
+///
+/// * `key_shares` - Key shares in any supported text format, at least `threshold` of them.
+/// * `ciphertext` - The ciphertext from `split_file`.
+///
+/// # Returns
+///
+/// The file contents.
+pub fn combine_file<S: AsRef<str>>(
+    key_shares: &[S],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, FileError> {
+    let key = share::combine(key_shares).map_err(FileError::Key)?;
+    decrypt_aes(&key, ciphertext)
+}
+
+/// Like `combine_file`, with the ciphertext spread over `ida` fragments.
+pub fn combine_dispersed<S: AsRef<str>>(
+    key_shares: &[S],
+    fragments: &[Fragment],
+) -> Result<Vec<u8>, FileError> {
+    let ciphertext = ida::recover(fragments).map_err(FileError::Fragments)?;
+    combine_file(key_shares, &ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::share::Format;
+
+    #[test]
+    fn shares_stay_small_and_the_file_comes_back() {
+        let contents: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let split = split_file(&contents, 3, 5);
+        let encoded: Vec<String> = split
+            .key_shares
+            .iter()
+            .map(|share| share.encode(Format::Hex))
+            .collect();
+        assert!(split
+            .key_shares
+            .iter()
+            .all(|share| share.payload.len() == KEY_LEN));
+        assert_eq!(split.ciphertext.len(), contents.len() + 28);
+
+        assert_eq!(
+            combine_file(&encoded[2..], &split.ciphertext),
+            Ok(contents.clone())
+        );
+
+        let fragments = ida::disperse(&split.ciphertext, 3, 5);
+        assert_eq!(fragments[0].data.len(), split.ciphertext.len().div_ceil(3));
+        assert_eq!(
+            combine_dispersed(&encoded[..3], &fragments[1..4]),
+            Ok(contents)
+        );
+
+        // A flipped ciphertext bit or too few key shares are caught
+        let mut tampered = split.ciphertext.clone();
+        tampered[100] ^= 1;
+        assert_eq!(
+            combine_file(&encoded[..3], &tampered),
+            Err(FileError::Decryption)
+        );
+        assert_eq!(
+            combine_file(&encoded[..2], &split.ciphertext),
+            Err(FileError::Key(CombineError::NotEnoughShares {
+                have: 2,
+                need: 3
+            }))
+        );
+    }
+}
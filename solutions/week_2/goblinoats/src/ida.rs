@@ -0,0 +1,238 @@
+//! Rabin's information dispersal over GF(2^8).
+//!
+//! The data is cut into chunks of t bytes, and each chunk is read as the coefficients of a
+//! polynomial of degree t - 1. Fragment x stores that polynomial's value at x for every chunk, so
+//! it holds |data| / t bytes, and any t fragments give back every polynomial by interpolation.
+//! Unlike `gf256` sharing there is no randomness: fewer than t fragments still leak information
+//! about the data, which is why `hybrid` only disperses ciphertext.
+//!
+//! A fragment is stored as
+//!
+//! ```text
+//! version (1) | x (1) | threshold (1) | data length (8) | fragment data
+//! ```
+
+use num_bigint::BigInt;
+
+use crate::gf256::{add, div, mul};
+use crate::shamir::ShamirError;
+use crate::share::{ShareFormatError, VERSION};
+
+const HEADER_LEN: usize = 11;
+
+/// One holder's piece of the dispersed data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fragment {
+    pub x: u8,
+    pub threshold: u8,
+    /// Length of the original data, which the last chunk was padded past.
+    pub length: u64,
+    pub data: Vec<u8>,
+}
+
+impl Fragment {
+    /// Serialises the fragment with its header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![VERSION, self.x, self.threshold];
+        bytes.extend_from_slice(&self.length.to_be_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Parses a serialised fragment.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Fragment, ShareFormatError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(ShareFormatError::Truncated);
+        }
+        if bytes[0] != VERSION {
+            return Err(ShareFormatError::UnsupportedVersion(bytes[0]));
+        }
+        if bytes[1] == 0 {
+            return Err(ShareFormatError::InvalidIndex);
+        }
+        Ok(Fragment {
+            x: bytes[1],
+            threshold: bytes[2],
+            length: u64::from_be_bytes(bytes[3..HEADER_LEN].try_into().unwrap()),
+            data: bytes[HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+/// Disperses data into fragments.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to disperse, of any length.
+/// * `threshold` - The number of fragments needed to recover the data.
+/// * `num_fragments` - The total number of fragments to create, at most 255.
+///
+/// # Returns
+///
+/// A vector of `Fragment`s with x-coordinates `1..=num_fragments`, each ceil(|data| / t) bytes.
+pub fn disperse(data: &[u8], threshold: usize, num_fragments: usize) -> Vec<Fragment> {
+    assert!(threshold >= 1, "threshold must be at least 1");
+    assert!(
+        threshold <= num_fragments,
+        "threshold can't exceed the number of fragments"
+    );
+    assert!(
+        num_fragments <= 255,
+        "GF(256) has only 255 non-zero x-coordinates"
+    );
+
+    (1..=num_fragments as u8)
+        .map(|x| Fragment {
+            x,
+            threshold: threshold as u8,
+            length: data.len() as u64,
+            data: data
+                .chunks(threshold)
+                .map(|chunk| {
+                    // Horner's rule; a short last chunk is implicitly padded with zeros
+                    chunk
+                        .iter()
+                        .rev()
+                        .fold(0u8, |y, &coefficient| add(mul(y, x), coefficient))
+                })
+                .collect::<Vec<u8>>(),
+        })
+        .collect()
+}
+
+/// Coefficients of the Lagrange basis polynomials L_j(X) = prod (X - x_m) / (x_j - x_m), lowest
+/// degree first, one row per x-coordinate.
+fn basis_polynomials(xs: &[u8]) -> Vec<Vec<u8>> {
+    xs.iter()
+        .enumerate()
+        .map(|(j, &x_j)| {
+            let mut polynomial = vec![1u8];
+            let mut denominator = 1u8;
+            for (_, &x_m) in xs.iter().enumerate().filter(|(m, _)| *m != j) {
+                // Multiply by (X + x_m), subtraction being addition in characteristic 2
+                let mut product = vec![0u8; polynomial.len() + 1];
+                for (k, &coefficient) in polynomial.iter().enumerate() {
+                    product[k] = add(product[k], mul(coefficient, x_m));
+                    product[k + 1] = add(product[k + 1], coefficient);
+                }
+                polynomial = product;
+                denominator = mul(denominator, add(x_j, x_m));
+            }
+            polynomial
+                .into_iter()
+                .map(|coefficient| div(coefficient, denominator))
+                .collect()
+        })
+        .collect()
+}
+
+/// Recovers the data from fragments.
+///
+/// # Arguments
+///
+/// * `fragments` - At least `threshold` fragments of the same data with distinct x-coordinates.
+///
+/// # Returns
+///
+/// The original data, or an error if there are too few fragments, they don't match, or an
+/// x-coordinate is zero or repeated.
+pub fn recover(fragments: &[Fragment]) -> Result<Vec<u8>, ShamirError> {
+    let first = fragments
+        .first()
+        .ok_or(ShamirError::NotEnoughShares { have: 0, need: 1 })?;
+    let threshold = first.threshold as usize;
+    if threshold == 0 {
+        return Err(ShamirError::InvalidThreshold {
+            threshold,
+            num_shares: fragments.len(),
+        });
+    }
+    if fragments.len() < threshold {
+        return Err(ShamirError::NotEnoughShares {
+            have: fragments.len(),
+            need: threshold,
+        });
+    }
+    let chunks = (first.length as usize).div_ceil(threshold);
+    for (i, fragment) in fragments.iter().enumerate() {
+        if fragment.threshold != first.threshold {
+            return Err(ShamirError::ParameterMismatch {
+                expected: threshold,
+                found: fragment.threshold as usize,
+            });
+        }
+        if fragment.length != first.length {
+            return Err(ShamirError::ParameterMismatch {
+                expected: first.length as usize,
+                found: fragment.length as usize,
+            });
+        }
+        if fragment.data.len() != chunks {
+            return Err(ShamirError::ParameterMismatch {
+                expected: chunks,
+                found: fragment.data.len(),
+            });
+        }
+        if fragment.x == 0 {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if fragments[..i].iter().any(|other| other.x == fragment.x) {
+            return Err(ShamirError::DuplicateIndex(BigInt::from(fragment.x)));
+        }
+    }
+
+    // The basis only depends on the x-coordinates, so it is shared by all chunks
+    let selected = &fragments[..threshold];
+    let xs: Vec<u8> = selected.iter().map(|fragment| fragment.x).collect();
+    let basis = basis_polynomials(&xs);
+
+    let mut data = Vec::with_capacity(chunks * threshold);
+    for c in 0..chunks {
+        for k in 0..threshold {
+            data.push(
+                selected
+                    .iter()
+                    .zip(&basis)
+                    .fold(0u8, |byte, (fragment, row)| {
+                        add(byte, mul(fragment.data[c], row[k]))
+                    }),
+            );
+        }
+    }
+    data.truncate(first.length as usize);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_of_fragments_recover_the_data() {
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 7 + 3) as u8).collect();
+        let fragments = disperse(&data, 4, 7);
+        assert!(fragments.iter().all(|fragment| fragment.data.len() == 250));
+
+        assert_eq!(recover(&fragments[3..]), Ok(data.clone()));
+        let mixed = [
+            fragments[6].clone(),
+            fragments[0].clone(),
+            fragments[4].clone(),
+            fragments[2].clone(),
+        ];
+        assert_eq!(recover(&mixed), Ok(data.clone()));
+
+        // A length that is not a multiple of the threshold survives the round trip
+        let short = disperse(&data[..13], 4, 5);
+        let parsed: Vec<Fragment> = short
+            .iter()
+            .map(|fragment| Fragment::from_bytes(&fragment.to_bytes()).unwrap())
+            .collect();
+        assert_eq!(recover(&parsed[1..]), Ok(data[..13].to_vec()));
+
+        assert_eq!(
+            recover(&fragments[..3]),
+            Err(ShamirError::NotEnoughShares { have: 3, need: 4 })
+        );
+    }
+}
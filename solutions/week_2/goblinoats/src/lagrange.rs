@@ -0,0 +1,209 @@
+//! Fast reconstruction with precomputed Lagrange weights.
+//!
+//! The secret is sum y_i w_i with w_i = L_i(0) = M(0) / (-x_i M'(x_i)) for M(X) = prod (X - x_i).
+//! The weights only depend on which shares are combined, so they can be computed once per index
+//! set and reused. All denominators are inverted together with Montgomery's trick, so a whole
+//! reconstruction costs one modular inversion instead of t. For large t, M'(x_i) comes from
+//! multipoint evaluation on a subproduct tree in O(t log^2 t) instead of O(t^2) products.
+
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+
+use crate::poly::{derivative, reduce, SubproductTree};
+use crate::shamir::{check_shares, modinv, ShamirError};
+
+/// From this many shares on the subproduct tree beats computing each denominator directly.
+const TREE_CUTOFF: usize = 64;
+
+/// With x-coordinates this small (like the default 1..=n) every x_i - x_j is a single word and the
+/// direct products stay cheaper than the tree for any practical share count.
+const SMALL_X_BITS: u64 = 64;
+
+/// Inverts every value with a single modular inversion (Montgomery's trick).
+///
+/// # Arguments
+///
+/// * `values` - The values to invert.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The inverses in the same order, or `None` if any value is zero modulo prime.
+pub fn batch_invert(values: &[BigInt], prime: &BigInt) -> Option<Vec<BigInt>> {
+    // prefix[i] = values[0] * ... * values[i - 1]
+    let mut prefix = Vec::with_capacity(values.len() + 1);
+    prefix.push(BigInt::one());
+    for value in values {
+        let next = (prefix.last().unwrap() * value) % prime;
+        prefix.push(next);
+    }
+
+    // Values may be negative; keeping them small in magnitude keeps the one inversion cheap
+    let mut inverse = modinv(&prefix.pop().unwrap(), prime)?;
+    let mut inverses = vec![BigInt::zero(); values.len()];
+    // Walk back: inverse of the product up to i times the prefix before i is values[i]^-1
+    for (i, value) in values.iter().enumerate().rev() {
+        inverses[i] = reduce(&inverse * &prefix[i], prime);
+        inverse = (inverse * value) % prime;
+    }
+    Some(inverses)
+}
+
+/// Lagrange weights for interpolating at zero from a fixed set of x-coordinates.
+#[derive(Clone, Debug)]
+pub struct LagrangeWeights {
+    xs: Vec<BigInt>,
+    weights: Vec<BigInt>,
+    prime: BigInt,
+}
+
+impl LagrangeWeights {
+    /// Precomputes the weights for the given x-coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `xs` - Distinct, non-zero x-coordinates in [0, prime) of the shares that will be combined.
+    /// * `prime` - A large prime number for modulo operations.
+    ///
+    /// # Returns
+    ///
+    /// The weights, or an error if an x-coordinate is zero, repeated or outside the field.
+    pub fn new(xs: &[BigInt], prime: &BigInt) -> Result<Self, ShamirError> {
+        let points: Vec<(BigInt, BigInt)> = xs.iter().map(|x| (x.clone(), BigInt::zero())).collect();
+        check_shares(&points, prime)?;
+        let xs = xs.to_vec();
+
+        // M'(x_i) = prod_{j != i} (x_i - x_j), either directly or by multipoint evaluation
+        let small_xs = xs.iter().all(|x| x.bits() <= SMALL_X_BITS);
+        let (m_at_zero, derivatives) = if xs.len() < TREE_CUTOFF || small_xs {
+            // Truncating % keeps products of small values small, unlike reducing into [0, prime)
+            let m_at_zero = xs.iter().fold(BigInt::one(), |acc, x| (acc * -x) % prime);
+            let derivatives = xs
+                .iter()
+                .enumerate()
+                .map(|(i, xi)| {
+                    xs.iter()
+                        .enumerate()
+                        .filter(|(j, _)| *j != i)
+                        .fold(BigInt::one(), |acc, (_, xj)| (acc * (xi - xj)) % prime)
+                })
+                .collect();
+            (m_at_zero, derivatives)
+        } else {
+            let tree = SubproductTree::new(&xs, prime);
+            let m_at_zero = tree.root()[0].clone();
+            (m_at_zero, tree.evaluate(&derivative(tree.root(), prime), prime))
+        };
+
+        // w_i = -M(0) / (x_i M'(x_i)); with valid x's only a composite modulus has no inverses
+        let denominators: Vec<BigInt> = xs
+            .iter()
+            .zip(&derivatives)
+            .map(|(x, d)| (x * d) % prime)
+            .collect();
+        let minus_m_at_zero = -m_at_zero;
+        let weights = batch_invert(&denominators, prime)
+            .ok_or(ShamirError::NotPrime)?
+            .into_iter()
+            .map(|inverse| reduce(inverse * &minus_m_at_zero, prime))
+            .collect();
+
+        Ok(LagrangeWeights {
+            xs,
+            weights,
+            prime: prime.clone(),
+        })
+    }
+
+    /// The x-coordinates the weights were computed for.
+    pub fn xs(&self) -> &[BigInt] {
+        &self.xs
+    }
+
+    /// Interpolates at zero from y-values in [0, prime) given in the order of `xs`, in O(t).
+    pub fn interpolate_at_zero(&self, ys: &[BigInt]) -> Result<BigInt, ShamirError> {
+        if ys.len() != self.weights.len() {
+            return Err(ShamirError::ParameterMismatch {
+                expected: self.weights.len(),
+                found: ys.len(),
+            });
+        }
+        if let Some(y) = ys.iter().find(|y| y.sign() == Sign::Minus || **y >= self.prime) {
+            return Err(ShamirError::ValueOutOfRange(y.clone()));
+        }
+        let sum = ys
+            .iter()
+            .zip(&self.weights)
+            .fold(BigInt::zero(), |acc, (y, w)| acc + y * w);
+        Ok(reduce(sum, &self.prime))
+    }
+}
+
+/// Reconstructs the secret like `reconstruct_secret`, with batch inversion and, for many shares
+/// with large x-coordinates, the subproduct tree.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of tuples containing x and y coordinates.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The reconstructed secret as a `BigInt`, or the same errors as `reconstruct_secret`.
+pub fn fast_reconstruct_secret(
+    shares: &[(BigInt, BigInt)],
+    prime: &BigInt,
+) -> Result<BigInt, ShamirError> {
+    check_shares(shares, prime)?;
+    let xs: Vec<BigInt> = shares.iter().map(|(x, _)| x.clone()).collect();
+    let ys: Vec<BigInt> = shares.iter().map(|(_, y)| y.clone()).collect();
+    LagrangeWeights::new(&xs, prime)?.interpolate_at_zero(&ys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shamir::{
+        create_shares, evaluate_polynomial, generate_coefficients, reconstruct_secret,
+    };
+    use num_bigint::RandBigInt;
+
+    #[test]
+    fn matches_reference_reconstruction() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let secret = BigInt::from(271828);
+        for threshold in [1, 5, 100] {
+            let shares = create_shares(threshold + 3, threshold, &secret, &prime);
+            let subset = &shares[3..];
+            assert_eq!(fast_reconstruct_secret(subset, &prime), Ok(secret.clone()));
+            assert_eq!(reconstruct_secret(subset, &prime), Ok(secret.clone()));
+        }
+
+        // Random 127-bit x-coordinates with enough shares take the subproduct tree path
+        let prime = BigInt::parse_bytes(b"170141183460469231731687303715884105727", 10).unwrap();
+        let coefficients = generate_coefficients(100, &secret, &prime);
+        let mut rng = rand::thread_rng();
+        let shares: Vec<(BigInt, BigInt)> = (0..100)
+            .map(|_| {
+                let x = rng.gen_bigint_range(&BigInt::one(), &prime);
+                let y = evaluate_polynomial(&x, &coefficients, &prime);
+                (x, y)
+            })
+            .collect();
+        assert!(shares.iter().any(|(x, _)| x.bits() > SMALL_X_BITS));
+        assert_eq!(fast_reconstruct_secret(&shares, &prime), Ok(secret.clone()));
+        assert_eq!(reconstruct_secret(&shares, &prime), Ok(secret));
+
+        let xs = [BigInt::from(1), BigInt::from(5), BigInt::from(1)];
+        assert_eq!(
+            LagrangeWeights::new(&xs, &prime).unwrap_err(),
+            ShamirError::DuplicateIndex(BigInt::from(1))
+        );
+        let weights = LagrangeWeights::new(&xs[..2], &prime).unwrap();
+        assert_eq!(
+            weights.interpolate_at_zero(&[BigInt::one()]),
+            Err(ShamirError::ParameterMismatch { expected: 2, found: 1 })
+        );
+        assert!(batch_invert(&[BigInt::from(3), BigInt::zero()], &prime).is_none());
+    }
+}
@@ -0,0 +1,20 @@
+pub mod dkg;
+pub mod enrollment;
+pub mod feldman;
+pub mod gf256;
+pub mod hierarchical;
+pub mod hybrid;
+pub mod ida;
+pub mod lagrange;
+pub mod mpc;
+pub mod multipoint;
+pub mod ntt;
+pub mod packed;
+pub mod pedersen;
+pub mod poly;
+pub mod refresh;
+pub mod robust;
+pub mod scheme;
+pub mod shamir;
+pub mod share;
+pub mod weighted;
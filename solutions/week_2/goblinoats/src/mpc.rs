@@ -0,0 +1,231 @@
+//! Arithmetic on shares and BGW multiplication.
+//!
+//! Shamir sharing is linear: adding the shares of two secrets held at the same x gives a share of
+//! their sum, and scaling or shifting a share does the same to the secret, all without talking to
+//! anyone. Multiplying two shares gives a point on the product of the polynomials, which shares
+//! the product of the secrets but has degree 2(t - 1), so it needs 2t - 1 holders and can't be
+//! multiplied again. BGW brings the degree back down: every holder reshares its product share
+//! under threshold t, and each holder combines the sub-shares it receives with the Lagrange
+//! coefficients at 0, which is exactly `refresh::reshare` onto the same holders.
+
+use num_bigint::BigInt;
+
+use crate::refresh::reshare;
+use crate::shamir::{create_shares, modp_2048_prime, reconstruct_secret, ShamirError};
+
+/// Reduces a value into [0, prime).
+fn reduce(value: BigInt, prime: &BigInt) -> BigInt {
+    ((value % prime) + prime) % prime
+}
+
+/// Rejects shares of different holders.
+fn same_holder(a: &(BigInt, BigInt), b: &(BigInt, BigInt)) -> Result<(), ShamirError> {
+    if a.0 != b.0 {
+        return Err(ShamirError::IndexMismatch {
+            expected: a.0.clone(),
+            found: b.0.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Adds two shares held by the same holder.
+///
+/// # Arguments
+///
+/// * `a` - A share of the first secret.
+/// * `b` - A share of the second secret at the same x-coordinate.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// A share of the sum, or an error if the x-coordinates differ.
+pub fn add(
+    a: &(BigInt, BigInt),
+    b: &(BigInt, BigInt),
+    prime: &BigInt,
+) -> Result<(BigInt, BigInt), ShamirError> {
+    same_holder(a, b)?;
+    Ok((a.0.clone(), reduce(&a.1 + &b.1, prime)))
+}
+
+/// Multiplies a share by a public constant, giving a share of the scaled secret.
+pub fn scale(share: &(BigInt, BigInt), constant: &BigInt, prime: &BigInt) -> (BigInt, BigInt) {
+    (share.0.clone(), reduce(&share.1 * constant, prime))
+}
+
+/// Adds a public constant to a share, giving a share of the shifted secret.
+///
+/// Every holder has to add the constant: it shifts the whole polynomial, not just a_0.
+pub fn add_const(share: &(BigInt, BigInt), constant: &BigInt, prime: &BigInt) -> (BigInt, BigInt) {
+    (share.0.clone(), reduce(&share.1 + constant, prime))
+}
+
+/// Multiplies two shares of the same holder locally.
+///
+/// # Returns
+///
+/// A share of the product under threshold 2t - 1, or an error if the x-coordinates differ.
+pub fn multiply_local(
+    a: &(BigInt, BigInt),
+    b: &(BigInt, BigInt),
+    prime: &BigInt,
+) -> Result<(BigInt, BigInt), ShamirError> {
+    same_holder(a, b)?;
+    Ok((a.0.clone(), (&a.1 * &b.1) % prime))
+}
+
+/// Multiplies two shared secrets with BGW among the holders of both sharings.
+///
+/// # Arguments
+///
+/// * `a` - Every holder's share of the first secret.
+/// * `b` - Every holder's share of the second secret, in the same holder order.
+/// * `threshold` - The threshold of both sharings, and of the result.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The holders' shares of the product under the same threshold, or an error if the holders
+/// differ or there are fewer than 2t - 1 of them.
+pub fn multiply(
+    a: &[(BigInt, BigInt)],
+    b: &[(BigInt, BigInt)],
+    threshold: usize,
+    prime: &BigInt,
+) -> Result<Vec<(BigInt, BigInt)>, ShamirError> {
+    if a.len() != b.len() {
+        return Err(ShamirError::ParameterMismatch {
+            expected: a.len(),
+            found: b.len(),
+        });
+    }
+    if threshold == 0 || a.len() < 2 * threshold - 1 {
+        return Err(ShamirError::NotEnoughShares {
+            have: a.len(),
+            need: (2 * threshold).max(2) - 1,
+        });
+    }
+    let products = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| multiply_local(a, b, prime))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Degree reduction: the product shares are a (2t - 1)-of-n sharing, reshare it as t-of-n
+    let xs: Vec<BigInt> = products.iter().map(|(x, _)| x.clone()).collect();
+    reshare(&products, 2 * threshold - 1, &xs, threshold, prime)
+}
+
+/// Five parties compute the sum and products of their salaries, opening only the results.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let threshold = 3;
+    let salaries = [52000, 61000, 48000, 75000, 58000].map(BigInt::from);
+
+    // Every party shares its input; row i holds all parties' shares of input i
+    let dealt: Vec<Vec<(BigInt, BigInt)>> = salaries
+        .iter()
+        .map(|salary| create_shares(salaries.len(), threshold, salary, &prime))
+        .collect();
+    println!(
+        "Party 1 holds shares of every salary, e.g. {}... of party 2's",
+        &dealt[1][0].1.to_string()[..12]
+    );
+
+    // Sum: each party adds up the shares it holds, then only the result is opened
+    let sum: Vec<(BigInt, BigInt)> = (0..salaries.len())
+        .map(|party| {
+            dealt[1..]
+                .iter()
+                .fold(dealt[0][party].clone(), |total, row| {
+                    add(&total, &row[party], &prime).unwrap()
+                })
+        })
+        .collect();
+    println!(
+        "Total payroll from 3 shares: {}",
+        reconstruct_secret(&sum[..threshold], &prime).unwrap()
+    );
+
+    // Public constants need no interaction: triple the payroll and add 1000
+    let raised: Vec<(BigInt, BigInt)> = sum
+        .iter()
+        .map(|share| {
+            add_const(
+                &scale(share, &BigInt::from(3), &prime),
+                &BigInt::from(1000),
+                &prime,
+            )
+        })
+        .collect();
+    println!(
+        "3 * payroll + 1000: {}",
+        reconstruct_secret(&raised[2..], &prime).unwrap()
+    );
+
+    // Product of parties 1 and 2's inputs, multiplied once more by party 3's
+    let product = multiply(&dealt[0], &dealt[1], threshold, &prime).unwrap();
+    println!(
+        "Salary 1 * salary 2 from 3 shares: {}",
+        reconstruct_secret(&product[..threshold], &prime).unwrap()
+    );
+    let product = multiply(&product, &dealt[2], threshold, &prime).unwrap();
+    println!(
+        "Salary 1 * salary 2 * salary 3 from 3 shares: {}",
+        reconstruct_secret(&product[1..4], &prime).unwrap()
+    );
+
+    // Without degree reduction 3 local products don't determine the product
+    let local: Vec<(BigInt, BigInt)> = dealt[0]
+        .iter()
+        .zip(&dealt[1])
+        .map(|(a, b)| multiply_local(a, b, &prime).unwrap())
+        .collect();
+    println!(
+        "3 local products give the product: {}",
+        reconstruct_secret(&local[..threshold], &prime) == Ok(&salaries[0] * &salaries[1])
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_compute_sums_and_products() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let (a, b, c) = (BigInt::from(12), BigInt::from(34), BigInt::from(56));
+        let a_shares = create_shares(5, 3, &a, &prime);
+        let b_shares = create_shares(5, 3, &b, &prime);
+        let c_shares = create_shares(5, 3, &c, &prime);
+
+        // 5a + b + 7
+        let linear: Vec<(BigInt, BigInt)> = a_shares
+            .iter()
+            .zip(&b_shares)
+            .map(|(a, b)| {
+                let sum = add(&scale(a, &BigInt::from(5), &prime), b, &prime).unwrap();
+                add_const(&sum, &BigInt::from(7), &prime)
+            })
+            .collect();
+        assert_eq!(
+            reconstruct_secret(&linear[2..], &prime),
+            Ok(BigInt::from(101))
+        );
+
+        // a * b * c stays 3-of-5
+        let ab = multiply(&a_shares, &b_shares, 3, &prime).unwrap();
+        let abc = multiply(&ab, &c_shares, 3, &prime).unwrap();
+        assert_eq!(reconstruct_secret(&abc[..3], &prime), Ok(a * b * c));
+
+        assert_eq!(
+            add(&a_shares[0], &b_shares[1], &prime),
+            Err(ShamirError::IndexMismatch {
+                expected: BigInt::from(1),
+                found: BigInt::from(2),
+            })
+        );
+        assert!(multiply(&a_shares[..4], &b_shares[..4], 3, &prime).is_err());
+    }
+}
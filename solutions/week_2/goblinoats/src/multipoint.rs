@@ -0,0 +1,137 @@
+//! Share generation by multipoint evaluation.
+//!
+//! `create_shares` evaluates the polynomial at each x on its own, which is t products per share.
+//! Here the x-coordinates are cut into blocks of t and each block is evaluated at once on a
+//! subproduct tree, which costs O(M(t) log t) per block instead of O(t^2). This pays off when the
+//! x-coordinates are full-size field elements; for the default 1..=n the Horner steps in
+//! `create_shares` only multiply by small numbers and stay cheaper.
+
+use std::collections::HashSet;
+
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+
+use crate::poly::SubproductTree;
+use crate::shamir::{generate_coefficients, ShamirError};
+
+/// Evaluates the polynomial at every x, one subproduct tree per block of `coefficients.len()`
+/// points.
+///
+/// # Arguments
+///
+/// * `coefficients` - The polynomial, lowest degree first.
+/// * `xs` - The points to evaluate at.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The values at `xs`, in the same order.
+pub fn evaluate_many(coefficients: &[BigInt], xs: &[BigInt], prime: &BigInt) -> Vec<BigInt> {
+    // Blocks of about the polynomial's length keep every tree balanced against the reduction
+    let block = coefficients.len().max(1);
+    xs.chunks(block)
+        .flat_map(|chunk| SubproductTree::new(chunk, prime).evaluate(coefficients, prime))
+        .collect()
+}
+
+/// Creates shares for the secret at caller-chosen x-coordinates.
+///
+/// # Arguments
+///
+/// * `xs` - The x-coordinates of the shares, distinct, non-zero and in [0, prime).
+/// * `threshold` - The minimum number of shares required to reconstruct the secret.
+/// * `secret` - The secret to be shared, in [0, prime).
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// A vector of tuples, each containing an x and y coordinate, or an error if the threshold does
+/// not fit the number of shares, the secret is out of range, or an x-coordinate is zero, repeated
+/// or outside the field.
+pub fn create_shares_at(
+    xs: &[BigInt],
+    threshold: usize,
+    secret: &BigInt,
+    prime: &BigInt,
+) -> Result<Vec<(BigInt, BigInt)>, ShamirError> {
+    if threshold == 0 || threshold > xs.len() {
+        return Err(ShamirError::InvalidThreshold {
+            threshold,
+            num_shares: xs.len(),
+        });
+    }
+    if secret.sign() == Sign::Minus || secret >= prime {
+        return Err(ShamirError::SecretOutOfRange);
+    }
+    let mut seen = HashSet::with_capacity(xs.len());
+    for x in xs {
+        if x.sign() == Sign::Minus || x >= prime {
+            return Err(ShamirError::ValueOutOfRange(x.clone()));
+        }
+        if x.is_zero() {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if !seen.insert(x) {
+            return Err(ShamirError::DuplicateIndex(x.clone()));
+        }
+    }
+
+    let coefficients = generate_coefficients(threshold, secret, prime);
+    Ok(xs
+        .iter()
+        .cloned()
+        .zip(evaluate_many(&coefficients, xs, prime))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shamir::evaluate_polynomial;
+    use num_bigint::RandBigInt;
+    use num_traits::One;
+
+    #[test]
+    fn matches_evaluate_polynomial() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let mut rng = rand::thread_rng();
+        let coefficients = generate_coefficients(40, &BigInt::from(5), &prime);
+        let xs: Vec<BigInt> = (0..130)
+            .map(|_| rng.gen_bigint_range(&BigInt::one(), &prime))
+            .collect();
+        let expected: Vec<BigInt> = xs
+            .iter()
+            .map(|x| evaluate_polynomial(x, &coefficients, &prime))
+            .collect();
+        assert_eq!(evaluate_many(&coefficients, &xs, &prime), expected);
+
+        let shares = create_shares_at(&xs[..3], 1, &BigInt::from(9), &prime).unwrap();
+        assert!(shares.iter().all(|(_, y)| *y == BigInt::from(9)));
+        assert!(evaluate_many(&[], &xs[..2], &prime)
+            .iter()
+            .all(Zero::is_zero));
+
+        // Points that would leak the secret or can't be combined are rejected
+        let secret = BigInt::from(9);
+        let with = |x: BigInt| vec![xs[0].clone(), x];
+        assert_eq!(
+            create_shares_at(&with(BigInt::zero()), 2, &secret, &prime),
+            Err(ShamirError::ZeroIndex)
+        );
+        assert_eq!(
+            create_shares_at(&with(xs[0].clone()), 2, &secret, &prime),
+            Err(ShamirError::DuplicateIndex(xs[0].clone()))
+        );
+        assert_eq!(
+            create_shares_at(&with(&prime + 1), 2, &secret, &prime),
+            Err(ShamirError::ValueOutOfRange(&prime + 1))
+        );
+        assert_eq!(
+            create_shares_at(&xs[..2], 3, &secret, &prime),
+            Err(ShamirError::InvalidThreshold {
+                threshold: 3,
+                num_shares: 2
+            })
+        );
+    }
+}
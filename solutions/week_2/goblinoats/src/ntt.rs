@@ -0,0 +1,176 @@
+//! Share generation with a number-theoretic transform over the Goldilocks prime.
+//!
+//! p = 2^64 - 2^32 + 1 has 2^32 | p - 1, so it has roots of unity of every power-of-two order up
+//! to 2^32 and field elements fit in a u64. Putting the shares at x = w^i for a primitive N-th
+//! root of unity w lets one NTT of size N evaluate the polynomial at all N points in
+//! O(N log N), which makes millions of shares practical.
+
+use rand::{thread_rng, Rng};
+
+/// The Goldilocks prime 2^64 - 2^32 + 1.
+pub const GOLDILOCKS: u64 = 0xffff_ffff_0000_0001;
+
+/// A generator of the multiplicative group modulo `GOLDILOCKS`.
+const GENERATOR: u64 = 7;
+
+/// The largest power of two dividing p - 1.
+const TWO_ADICITY: u32 = 32;
+
+/// Multiplies modulo p through a 128-bit product.
+pub fn mul(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % GOLDILOCKS as u128) as u64
+}
+
+/// Adds modulo p for inputs already below p.
+pub fn add(a: u64, b: u64) -> u64 {
+    let (sum, overflow) = a.overflowing_add(b);
+    // On overflow sum + 2^64 is the true value and 2^64 = 2^32 - 1 mod p
+    let sum = if overflow { sum + 0xffff_ffff } else { sum };
+    if sum >= GOLDILOCKS {
+        sum - GOLDILOCKS
+    } else {
+        sum
+    }
+}
+
+/// Subtracts modulo p for inputs already below p.
+pub fn sub(a: u64, b: u64) -> u64 {
+    if a >= b {
+        a - b
+    } else {
+        GOLDILOCKS - (b - a)
+    }
+}
+
+/// Computes base^exponent modulo p by square-and-multiply.
+pub fn pow(mut base: u64, mut exponent: u64) -> u64 {
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// A primitive root of unity of order 2^log_n.
+pub fn root_of_unity(log_n: u32) -> u64 {
+    assert!(log_n <= TWO_ADICITY, "p - 1 has no larger power-of-two factor");
+    pow(GENERATOR, (GOLDILOCKS - 1) >> log_n)
+}
+
+/// In-place iterative radix-2 NTT: afterwards `values[i]` is the polynomial at w^i.
+///
+/// # Arguments
+///
+/// * `values` - The coefficients, lowest degree first, with a power-of-two length.
+pub fn ntt(values: &mut [u64]) {
+    let n = values.len();
+    assert!(n.is_power_of_two(), "NTT length must be a power of two");
+    let log_n = n.trailing_zeros();
+
+    // Bit-reversal permutation, then butterflies of doubling span
+    for i in 0..n {
+        let j = if log_n == 0 {
+            0
+        } else {
+            i.reverse_bits() >> (usize::BITS - log_n)
+        };
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let mut span = 1;
+    let mut level = 1;
+    while span < n {
+        let w = root_of_unity(level);
+        for start in (0..n).step_by(2 * span) {
+            let mut twiddle = 1;
+            for i in start..start + span {
+                let t = mul(values[i + span], twiddle);
+                values[i + span] = sub(values[i], t);
+                values[i] = add(values[i], t);
+                twiddle = mul(twiddle, w);
+            }
+        }
+        span *= 2;
+        level += 1;
+    }
+}
+
+/// Creates shares at the first `num_shares` powers of a root of unity with one NTT.
+///
+/// # Arguments
+///
+/// * `num_shares` - The total number of shares to create, at most 2^32.
+/// * `threshold` - The minimum number of shares required to reconstruct the secret.
+/// * `secret` - The secret to be shared, smaller than `GOLDILOCKS`.
+///
+/// # Returns
+///
+/// A vector of (x, y) tuples with x = w^i for i in 0..num_shares.
+pub fn create_shares_ntt(num_shares: usize, threshold: usize, secret: u64) -> Vec<(u64, u64)> {
+    assert!(secret < GOLDILOCKS, "secret must be smaller than the prime");
+    assert!(
+        threshold >= 1 && threshold <= num_shares,
+        "threshold must be between 1 and the number of shares"
+    );
+    let size = num_shares.next_power_of_two();
+    let log_size = size.trailing_zeros();
+
+    let mut rng = thread_rng();
+    let mut values = vec![0u64; size];
+    values[0] = secret;
+    for value in values.iter_mut().take(threshold).skip(1) {
+        *value = rng.gen_range(0..GOLDILOCKS);
+    }
+    ntt(&mut values);
+
+    let w = root_of_unity(log_size);
+    let mut x = 1;
+    values
+        .into_iter()
+        .take(num_shares)
+        .map(|y| {
+            let share = (x, y);
+            x = mul(x, w);
+            share
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lagrange::fast_reconstruct_secret;
+    use crate::shamir::{evaluate_polynomial, reconstruct_secret};
+    use num_bigint::BigInt;
+
+    #[test]
+    fn ntt_matches_horner() {
+        let prime = BigInt::from(GOLDILOCKS);
+        assert_eq!(add(GOLDILOCKS - 1, GOLDILOCKS - 1), GOLDILOCKS - 2);
+        assert_eq!(pow(root_of_unity(TWO_ADICITY), 1 << 31), GOLDILOCKS - 1);
+
+        let coefficients: Vec<u64> = vec![3, GOLDILOCKS - 5, 17, 0, 1 << 40, 9];
+        let mut values = coefficients.clone();
+        values.resize(16, 0);
+        ntt(&mut values);
+        let big_coefficients: Vec<BigInt> = coefficients.iter().map(|&c| BigInt::from(c)).collect();
+        let w = root_of_unity(4);
+        for (i, value) in values.iter().enumerate() {
+            let x = BigInt::from(pow(w, i as u64));
+            assert_eq!(BigInt::from(*value), evaluate_polynomial(&x, &big_coefficients, &prime));
+        }
+
+        let secret = 0x1234_5678_9abc_def0;
+        let shares: Vec<(BigInt, BigInt)> = create_shares_ntt(1000, 7, secret)
+            .into_iter()
+            .map(|(x, y)| (BigInt::from(x), BigInt::from(y)))
+            .collect();
+        assert_eq!(reconstruct_secret(&shares[500..507], &prime), Ok(BigInt::from(secret)));
+        assert_eq!(fast_reconstruct_secret(&shares[993..], &prime), Ok(BigInt::from(secret)));
+    }
+}
@@ -0,0 +1,216 @@
+//! Packed (Franklin-Yung) secret sharing: k secrets per polynomial.
+//!
+//! Plain Shamir spends one polynomial and n shares on every secret. Here k secrets sit at k fixed
+//! points -1, ..., -k of a single polynomial, t more points -k-1, ..., -k-t get random values,
+//! and the shares are the polynomial at 1..=n. The polynomial has degree t + k - 1, so any t shares
+//! are independent of the secrets (privacy threshold t) while t + k shares determine all of them
+//! (reconstruction threshold t + k). Each share is one field element for all k secrets.
+
+use num_bigint::{BigInt, RandBigInt, Sign};
+use num_traits::Zero;
+use rand::thread_rng;
+
+use crate::shamir::{interpolate_at, modp_2048_prime, ShamirError};
+
+/// Parameters of a packed sharing.
+#[derive(Clone, Debug)]
+pub struct PackedScheme {
+    secrets: usize,
+    privacy: usize,
+    prime: BigInt,
+}
+
+impl PackedScheme {
+    /// Creates a scheme packing `secrets` values per polynomial.
+    ///
+    /// # Arguments
+    ///
+    /// * `secrets` - The number of secrets k per polynomial.
+    /// * `privacy` - The privacy threshold t: t shares reveal nothing about the secrets.
+    /// * `prime` - A large prime number for modulo operations.
+    ///
+    /// # Returns
+    ///
+    /// The scheme, or an error if k or t is zero or the field is too small for the points.
+    pub fn new(secrets: usize, privacy: usize, prime: &BigInt) -> Result<Self, ShamirError> {
+        if secrets == 0 || privacy == 0 {
+            return Err(ShamirError::InvalidThreshold {
+                threshold: privacy,
+                num_shares: secrets,
+            });
+        }
+        if BigInt::from(secrets + privacy) >= *prime {
+            return Err(ShamirError::ValueOutOfRange(BigInt::from(
+                secrets + privacy,
+            )));
+        }
+        Ok(PackedScheme {
+            secrets,
+            privacy,
+            prime: prime.clone(),
+        })
+    }
+
+    /// The degree t + k - 1 of the sharing polynomial.
+    pub fn degree(&self) -> usize {
+        self.privacy + self.secrets - 1
+    }
+
+    /// The number of shares t + k needed to recover the secrets.
+    pub fn reconstruction_threshold(&self) -> usize {
+        self.privacy + self.secrets
+    }
+
+    /// The privacy threshold t.
+    pub fn privacy_threshold(&self) -> usize {
+        self.privacy
+    }
+
+    /// The point -i mod prime that holds the i-th secret (1-based) or, above k, a random value.
+    fn anchor(&self, i: usize) -> BigInt {
+        &self.prime - i
+    }
+
+    /// Shares k secrets with one polynomial.
+    ///
+    /// # Arguments
+    ///
+    /// * `secrets` - Exactly k secrets, each in [0, prime).
+    /// * `num_shares` - The number of shares n, at least t + k.
+    ///
+    /// # Returns
+    ///
+    /// The shares at x = 1..=n, or an error if the number of secrets, a secret, or n does not fit
+    /// the scheme.
+    pub fn share(
+        &self,
+        secrets: &[BigInt],
+        num_shares: usize,
+    ) -> Result<Vec<(BigInt, BigInt)>, ShamirError> {
+        if secrets.len() != self.secrets {
+            return Err(ShamirError::ParameterMismatch {
+                expected: self.secrets,
+                found: secrets.len(),
+            });
+        }
+        if num_shares < self.reconstruction_threshold() {
+            return Err(ShamirError::InvalidThreshold {
+                threshold: self.reconstruction_threshold(),
+                num_shares,
+            });
+        }
+        // Share points 1..=n must stay clear of the anchors -1, ..., -(k + t)
+        if BigInt::from(num_shares + self.reconstruction_threshold()) >= self.prime {
+            return Err(ShamirError::ValueOutOfRange(BigInt::from(num_shares)));
+        }
+        if secrets
+            .iter()
+            .any(|s| s.sign() == Sign::Minus || *s >= self.prime)
+        {
+            return Err(ShamirError::SecretOutOfRange);
+        }
+
+        let mut rng = thread_rng();
+        let anchors: Vec<(BigInt, BigInt)> = (1..=self.reconstruction_threshold())
+            .map(|i| {
+                let value = match secrets.get(i - 1) {
+                    Some(secret) => secret.clone(),
+                    None => rng.gen_bigint_range(&BigInt::zero(), &self.prime),
+                };
+                (self.anchor(i), value)
+            })
+            .collect();
+
+        (1..=num_shares)
+            .map(|x| {
+                let x = BigInt::from(x);
+                let y = interpolate_at(&anchors, &x, &self.prime)?;
+                Ok((x, y))
+            })
+            .collect()
+    }
+
+    /// Recovers all k secrets from at least t + k shares.
+    ///
+    /// # Arguments
+    ///
+    /// * `shares` - A slice of tuples containing x and y coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The secrets in order, or an error if there are too few shares or they are invalid.
+    pub fn reconstruct(&self, shares: &[(BigInt, BigInt)]) -> Result<Vec<BigInt>, ShamirError> {
+        let need = self.reconstruction_threshold();
+        if shares.len() < need {
+            return Err(ShamirError::NotEnoughShares {
+                have: shares.len(),
+                need,
+            });
+        }
+        (1..=self.secrets)
+            .map(|i| interpolate_at(&shares[..need], &self.anchor(i), &self.prime))
+            .collect()
+    }
+}
+
+/// Packs 8 secrets into one set of 15 shares with privacy threshold 3.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let scheme = PackedScheme::new(8, 3, &prime).unwrap();
+    let secrets: Vec<BigInt> = (1..=8).map(|i| BigInt::from(1000 * i + 7)).collect();
+    let shares = scheme.share(&secrets, 15).unwrap();
+
+    println!(
+        "{} secrets, privacy threshold {}, reconstruction threshold {}",
+        secrets.len(),
+        scheme.privacy_threshold(),
+        scheme.reconstruction_threshold()
+    );
+    println!(
+        "Field elements sent: {} packed vs {} with one polynomial per secret",
+        shares.len(),
+        shares.len() * secrets.len()
+    );
+    println!(
+        "Shares 5-15 recover: {:?}",
+        scheme
+            .reconstruct(&shares[4..])
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    );
+    if let Err(error) = scheme.reconstruct(&shares[..10]) {
+        println!("Shares 1-10: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_and_recovers_many_secrets() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let scheme = PackedScheme::new(4, 2, &prime).unwrap();
+        assert_eq!(scheme.degree(), 5);
+        let secrets: Vec<BigInt> = [11, 0, 2305843009213693950u64, 42]
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        let shares = scheme.share(&secrets, 9).unwrap();
+        assert_eq!(scheme.reconstruct(&shares[3..]), Ok(secrets.clone()));
+        assert_eq!(scheme.reconstruct(&shares[..6]), Ok(secrets.clone()));
+        assert_eq!(
+            scheme.reconstruct(&shares[..5]),
+            Err(ShamirError::NotEnoughShares { have: 5, need: 6 })
+        );
+        assert_eq!(
+            scheme.share(&secrets[..3], 9),
+            Err(ShamirError::ParameterMismatch {
+                expected: 4,
+                found: 3
+            })
+        );
+    }
+}
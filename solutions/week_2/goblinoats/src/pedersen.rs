@@ -0,0 +1,337 @@
+//! Pedersen verifiable secret sharing.
+//!
+//! The dealer shares the secret with f and a random blinding polynomial r, and publishes
+//! C_i = g^{a_i} h^{b_i}. Since nobody knows log_g(h), C_0 = g^secret h^{b_0} says nothing about
+//! the secret, yet a holder of (x, f(x), r(x)) can still check g^{f(x)} h^{r(x)} == prod C_i^{x^i}.
+//! Holders who receive a bad share complain, and the dealer must reveal the disputed share in
+//! public or be disqualified.
+
+use std::fmt;
+
+use num_bigint::{BigInt, RandBigInt, Sign};
+use num_traits::{One, Zero};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::feldman::{evaluate_commitments, Group, VssError};
+use crate::shamir::{evaluate_polynomial, generate_coefficients_with_rng, reconstruct_secret};
+
+/// A share of the secret polynomial together with the matching share of the blinding polynomial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PedersenShare {
+    pub x: BigInt,
+    pub y: BigInt,
+    pub blinding: BigInt,
+}
+
+/// Derives a second generator h with unknown discrete log to g by hashing into the subgroup.
+///
+/// # Arguments
+///
+/// * `group` - The group to derive the generator in.
+///
+/// # Returns
+///
+/// A quadratic residue h mod p, fixed for the group.
+pub fn second_generator(group: &Group) -> BigInt {
+    // Expand SHA-256 to 128 bits more than p so the reduction is close to uniform
+    let mut expanded = Vec::new();
+    let mut counter = 0u32;
+    while expanded.len() * 8 < group.p.bits() as usize + 128 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"pedersen-vss-h");
+        hasher.update(counter.to_be_bytes());
+        hasher.update(group.p.to_signed_bytes_be());
+        expanded.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    let h = BigInt::from_bytes_be(Sign::Plus, &expanded) % &group.p;
+    // Squaring lands in the subgroup of order q
+    group.pow(&h, &BigInt::from(2))
+}
+
+/// The dealer's secret and blinding polynomials and the published commitments.
+pub struct Dealer {
+    coefficients: Vec<BigInt>,
+    blinding: Vec<BigInt>,
+    pub commitments: Vec<BigInt>,
+}
+
+impl Dealer {
+    /// Picks both polynomials and commits to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The group to commit in; shares are taken modulo `group.q`.
+    /// * `h` - The second generator from `second_generator`.
+    /// * `threshold` - The minimum number of shares required to reconstruct the secret.
+    /// * `secret` - The secret to be shared, smaller than `group.q`.
+    pub fn new(group: &Group, h: &BigInt, threshold: usize, secret: &BigInt) -> Self {
+        Dealer::with_rng(group, h, threshold, secret, &mut rand::thread_rng())
+    }
+
+    /// Picks both polynomials with the given random number generator, like `new`.
+    pub fn with_rng<R: Rng + ?Sized>(
+        group: &Group,
+        h: &BigInt,
+        threshold: usize,
+        secret: &BigInt,
+        rng: &mut R,
+    ) -> Self {
+        assert!(
+            *secret >= BigInt::zero() && *secret < group.q,
+            "secret must be in [0, q)"
+        );
+        let coefficients = generate_coefficients_with_rng(threshold, secret, &group.q, rng);
+        // The blinding polynomial has a random constant term too, which is what hides the secret
+        let blinding_constant = rng.gen_bigint_range(&BigInt::zero(), &group.q);
+        let blinding = generate_coefficients_with_rng(threshold, &blinding_constant, &group.q, rng);
+        let commitments = coefficients
+            .iter()
+            .zip(&blinding)
+            .map(|(a, b)| (group.pow(&group.g, a) * group.pow(h, b)) % &group.p)
+            .collect();
+        Dealer {
+            coefficients,
+            blinding,
+            commitments,
+        }
+    }
+
+    /// The Feldman commitments g^{a_i} to the secret polynomial alone, which reveal g^secret.
+    pub fn feldman_commitments(&self, group: &Group) -> Vec<BigInt> {
+        self.coefficients
+            .iter()
+            .map(|coefficient| group.pow(&group.g, coefficient))
+            .collect()
+    }
+
+    /// Computes the share for the holder at x. The same call answers complaints in public.
+    pub fn share_for(&self, group: &Group, x: &BigInt) -> PedersenShare {
+        PedersenShare {
+            x: x.clone(),
+            y: evaluate_polynomial(x, &self.coefficients, &group.q),
+            blinding: evaluate_polynomial(x, &self.blinding, &group.q),
+        }
+    }
+}
+
+/// Checks a share against the dealer's commitments.
+///
+/// # Arguments
+///
+/// * `group` - The group the commitments live in.
+/// * `h` - The second generator.
+/// * `share` - The share to check.
+/// * `commitments` - The dealer's published commitments.
+///
+/// # Returns
+///
+/// `true` if x, y and blinding are in range and g^y h^blinding equals the commitment to x.
+pub fn verify_share(group: &Group, h: &BigInt, share: &PedersenShare, commitments: &[BigInt]) -> bool {
+    let in_range = group.is_exponent(&share.y) && group.is_exponent(&share.blinding);
+    if share.x.is_zero() || !group.is_exponent(&share.x) || !in_range {
+        return false;
+    }
+    let committed = (group.pow(&group.g, &share.y) * group.pow(h, &share.blinding)) % &group.p;
+    committed == evaluate_commitments(group, commitments, &share.x)
+}
+
+/// Why the dealer is disqualified during complaint handling.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DealerFault {
+    /// The dealer did not reveal the share the holder at x complained about.
+    NoAnswer { x: BigInt },
+    /// The revealed share for x does not match the commitments either.
+    InvalidAnswer { x: BigInt },
+}
+
+impl fmt::Display for DealerFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DealerFault::NoAnswer { x } => {
+                write!(f, "dealer did not answer the complaint from x = {}", x)
+            }
+            DealerFault::InvalidAnswer { x } => {
+                write!(f, "dealer revealed an invalid share for x = {}", x)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DealerFault {}
+
+/// Resolves the complaints raised after dealing.
+///
+/// Every complaint names an x whose share failed verification. The dealer answers with the share
+/// for that x in public, and everyone checks it against the commitments. Complainants adopt the
+/// revealed shares; a missing or invalid answer disqualifies the dealer.
+///
+/// # Arguments
+///
+/// * `group` - The group the commitments live in.
+/// * `h` - The second generator.
+/// * `commitments` - The dealer's published commitments.
+/// * `complaints` - The x-coordinates of holders who complained.
+/// * `answer` - The dealer's public answer for a complaint, `None` if the dealer stays silent.
+///
+/// # Returns
+///
+/// The revealed shares, one per complaint, or the first fault found.
+pub fn resolve_complaints<F>(
+    group: &Group,
+    h: &BigInt,
+    commitments: &[BigInt],
+    complaints: &[BigInt],
+    mut answer: F,
+) -> Result<Vec<PedersenShare>, DealerFault>
+where
+    F: FnMut(&BigInt) -> Option<PedersenShare>,
+{
+    complaints
+        .iter()
+        .map(|x| {
+            let revealed = answer(x).ok_or_else(|| DealerFault::NoAnswer { x: x.clone() })?;
+            if revealed.x != *x || !verify_share(group, h, &revealed, commitments) {
+                return Err(DealerFault::InvalidAnswer { x: x.clone() });
+            }
+            Ok(revealed)
+        })
+        .collect()
+}
+
+/// Reconstructs the secret from the shares that verify against the commitments.
+///
+/// # Arguments
+///
+/// * `group` - The group the commitments live in.
+/// * `h` - The second generator.
+/// * `shares` - The shares to reconstruct from; failing shares are dropped.
+/// * `commitments` - The dealer's published commitments, one per coefficient.
+///
+/// # Returns
+///
+/// The secret, or an error listing the rejected shares if fewer than `threshold` verified.
+pub fn reconstruct_verified(
+    group: &Group,
+    h: &BigInt,
+    shares: &[PedersenShare],
+    commitments: &[BigInt],
+) -> Result<BigInt, VssError> {
+    let threshold = commitments.len();
+    let (valid, rejected): (Vec<&PedersenShare>, Vec<&PedersenShare>) = shares
+        .iter()
+        .partition(|share| verify_share(group, h, share, commitments));
+    if valid.len() < threshold {
+        return Err(VssError::NotEnoughValidShares {
+            valid: valid.len(),
+            threshold,
+            rejected: rejected.into_iter().map(|share| share.x.clone()).collect(),
+        });
+    }
+    let points: Vec<(BigInt, BigInt)> = valid[..threshold]
+        .iter()
+        .map(|share| (share.x.clone(), share.y.clone()))
+        .collect();
+    reconstruct_secret(&points, &group.q).map_err(VssError::Reconstruction)
+}
+
+/// Runs a dealing with a complaint, first against an honest and then a cheating dealer.
+pub fn run_demo() {
+    let group = Group::modp_2048();
+    let h = second_generator(&group);
+    // A low-entropy secret, g^secret from Feldman would give it away by brute force
+    let secret = BigInt::from(7);
+    let dealer = Dealer::new(&group, &h, 3, &secret);
+
+    let mut shares: Vec<PedersenShare> = (1..=5)
+        .map(|i| dealer.share_for(&group, &BigInt::from(i)))
+        .collect();
+    // The share for x = 4 is corrupted on the way
+    shares[3].y = (&shares[3].y + BigInt::one()) % &group.q;
+
+    let complaints: Vec<BigInt> = shares
+        .iter()
+        .filter(|share| !verify_share(&group, &h, share, &dealer.commitments))
+        .map(|share| share.x.clone())
+        .collect();
+    println!("Complaints from x = {:?}", complaints);
+
+    // An honest dealer reveals the disputed share and the complainant adopts it
+    match resolve_complaints(&group, &h, &dealer.commitments, &complaints, |x| {
+        Some(dealer.share_for(&group, x))
+    }) {
+        Ok(revealed) => {
+            for share in revealed {
+                let index = shares.iter().position(|s| s.x == share.x).unwrap();
+                shares[index] = share;
+            }
+            println!("Honest dealer answered all complaints");
+        }
+        Err(fault) => println!("Dealer disqualified: {}", fault),
+    }
+
+    // A cheating dealer can't produce a different share that still matches the commitments
+    let fault = resolve_complaints(&group, &h, &dealer.commitments, &complaints, |x| {
+        let mut share = dealer.share_for(&group, x);
+        share.y += 1;
+        Some(share)
+    });
+    if let Err(fault) = fault {
+        println!("Cheating dealer disqualified: {}", fault);
+    }
+
+    match reconstruct_verified(&group, &h, &shares[2..], &dealer.commitments) {
+        Ok(recovered) => println!("Recovered secret from shares 3-5: {}", recovered),
+        Err(error) => println!("Reconstruction refused: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complaints_and_reconstruction() {
+        let group = Group::modp_2048();
+        let h = second_generator(&group);
+        let secret = BigInt::from(1);
+        let dealer = Dealer::new(&group, &h, 2, &secret);
+        let mut shares: Vec<PedersenShare> = (1..=3)
+            .map(|i| dealer.share_for(&group, &BigInt::from(i)))
+            .collect();
+        assert!(shares.iter().all(|share| verify_share(&group, &h, share, &dealer.commitments)));
+
+        // The commitment to the constant term is not g^secret
+        assert_ne!(dealer.commitments[0], group.pow(&group.g, &secret));
+
+        // Out-of-range values are rejected instead of reaching modpow
+        let mut negative = shares[1].clone();
+        negative.blinding = BigInt::from(-1);
+        assert!(!verify_share(&group, &h, &negative, &dealer.commitments));
+
+        shares[0].blinding += 1;
+        let complaints = vec![BigInt::from(1)];
+        assert_eq!(
+            resolve_complaints(&group, &h, &dealer.commitments, &complaints, |_| None),
+            Err(DealerFault::NoAnswer { x: BigInt::from(1) })
+        );
+        let revealed = resolve_complaints(&group, &h, &dealer.commitments, &complaints, |x| {
+            Some(dealer.share_for(&group, x))
+        })
+        .unwrap();
+        assert_eq!(
+            reconstruct_verified(&group, &h, &[shares[0].clone(), shares[2].clone()], &dealer.commitments),
+            Err(VssError::NotEnoughValidShares {
+                valid: 1,
+                threshold: 2,
+                rejected: vec![BigInt::from(1)],
+            })
+        );
+        shares[0] = revealed[0].clone();
+        assert_eq!(
+            reconstruct_verified(&group, &h, &shares, &dealer.commitments),
+            Ok(secret)
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Dense polynomial arithmetic modulo a prime, for fast interpolation and multipoint evaluation.
+//!
+//! Polynomials are coefficient vectors, lowest degree first, with every coefficient in [0, prime).
+//! Multiplication uses Karatsuba above a small cutoff, division uses Newton iteration on the
+//! reversed divisor, and a subproduct tree over the points gives multipoint evaluation in
+//! O(M(n) log n) instead of O(n^2).
+
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+
+use crate::shamir::modinv;
+
+/// Below this length schoolbook multiplication beats Karatsuba on 2048-bit coefficients.
+const KARATSUBA_CUTOFF: usize = 16;
+
+/// Below this divisor length schoolbook long division beats Newton iteration.
+const NEWTON_CUTOFF: usize = 32;
+
+/// Reduces a value into [0, prime).
+pub fn reduce(value: BigInt, prime: &BigInt) -> BigInt {
+    let value = value % prime;
+    if value < BigInt::zero() {
+        value + prime
+    } else {
+        value
+    }
+}
+
+/// Drops leading zero coefficients.
+fn trim(mut a: Vec<BigInt>) -> Vec<BigInt> {
+    while a.last().is_some_and(Zero::is_zero) {
+        a.pop();
+    }
+    a
+}
+
+/// Schoolbook product without reduction.
+fn mul_schoolbook(a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![BigInt::zero(); a.len() + b.len() - 1];
+    for (i, ai) in a.iter().enumerate() {
+        if ai.is_zero() {
+            continue;
+        }
+        for (j, bj) in b.iter().enumerate() {
+            product[i + j] += ai * bj;
+        }
+    }
+    product
+}
+
+/// Adds `b` into `a` starting at coefficient `offset`, growing `a` as needed.
+fn add_into(a: &mut Vec<BigInt>, b: &[BigInt], offset: usize) {
+    if a.len() < offset + b.len() {
+        a.resize(offset + b.len(), BigInt::zero());
+    }
+    for (i, bi) in b.iter().enumerate() {
+        a[offset + i] += bi;
+    }
+}
+
+/// Karatsuba product without reduction. Intermediate coefficients may be negative.
+fn mul_karatsuba(a: &[BigInt], b: &[BigInt]) -> Vec<BigInt> {
+    if a.len() < KARATSUBA_CUTOFF || b.len() < KARATSUBA_CUTOFF {
+        return mul_schoolbook(a, b);
+    }
+    let m = a.len().max(b.len()) / 2;
+
+    // Unbalanced operands: split only the longer one
+    if a.len() <= m || b.len() <= m {
+        let (short, long) = if a.len() <= m { (a, b) } else { (b, a) };
+        let mut product = mul_karatsuba(short, &long[..m]);
+        add_into(&mut product, &mul_karatsuba(short, &long[m..]), m);
+        return product;
+    }
+
+    let (a0, a1) = a.split_at(m);
+    let (b0, b1) = b.split_at(m);
+    let z0 = mul_karatsuba(a0, b0);
+    let z2 = mul_karatsuba(a1, b1);
+
+    let mut a_sum = a1.to_vec();
+    add_into(&mut a_sum, a0, 0);
+    let mut b_sum = b1.to_vec();
+    add_into(&mut b_sum, b0, 0);
+    let mut z1 = mul_karatsuba(&a_sum, &b_sum);
+    for (i, c) in z0.iter().enumerate() {
+        z1[i] -= c;
+    }
+    for (i, c) in z2.iter().enumerate() {
+        z1[i] -= c;
+    }
+
+    let mut product = z0;
+    add_into(&mut product, &z1, m);
+    add_into(&mut product, &z2, 2 * m);
+    product.truncate(a.len() + b.len() - 1);
+    product
+}
+
+/// Multiplies two polynomials modulo prime.
+pub fn mul(a: &[BigInt], b: &[BigInt], prime: &BigInt) -> Vec<BigInt> {
+    trim(
+        mul_karatsuba(a, b)
+            .into_iter()
+            .map(|c| reduce(c, prime))
+            .collect(),
+    )
+}
+
+/// Subtracts b from a modulo prime.
+pub fn sub(a: &[BigInt], b: &[BigInt], prime: &BigInt) -> Vec<BigInt> {
+    let length = a.len().max(b.len());
+    let zero = BigInt::zero();
+    trim(
+        (0..length)
+            .map(|i| reduce(a.get(i).unwrap_or(&zero) - b.get(i).unwrap_or(&zero), prime))
+            .collect(),
+    )
+}
+
+/// Formal derivative.
+pub fn derivative(a: &[BigInt], prime: &BigInt) -> Vec<BigInt> {
+    trim(
+        a.iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| (c * BigInt::from(i)) % prime)
+            .collect(),
+    )
+}
+
+/// Computes a^-1 mod X^n by Newton iteration, doubling the precision every step.
+fn inverse_series(a: &[BigInt], n: usize, prime: &BigInt) -> Vec<BigInt> {
+    let mut inverse = vec![modinv(&a[0], prime).expect("constant term must be invertible")];
+    let mut precision = 1;
+    while precision < n {
+        precision = (2 * precision).min(n);
+        // g <- g (2 - a g) mod X^precision
+        let mut error = mul(&a[..a.len().min(precision)], &inverse, prime);
+        error.truncate(precision);
+        let mut correction: Vec<BigInt> = error.into_iter().map(|c| reduce(-c, prime)).collect();
+        if correction.is_empty() {
+            correction.push(BigInt::zero());
+        }
+        correction[0] = reduce(&correction[0] + 2, prime);
+        inverse = mul(&inverse, &correction, prime);
+        inverse.truncate(precision);
+    }
+    inverse
+}
+
+/// Schoolbook long division.
+fn divrem_schoolbook(a: &[BigInt], b: &[BigInt], prime: &BigInt) -> (Vec<BigInt>, Vec<BigInt>) {
+    let degree = b.len() - 1;
+    let lead_inverse = modinv(&b[degree], prime).expect("leading coefficient must be invertible");
+    let mut remainder = a.to_vec();
+    let mut quotient = vec![BigInt::zero(); a.len() - degree];
+    for i in (0..quotient.len()).rev() {
+        let coefficient = (&remainder[i + degree] * &lead_inverse) % prime;
+        if !coefficient.is_zero() {
+            for (j, bj) in b.iter().enumerate() {
+                remainder[i + j] = reduce(&remainder[i + j] - &coefficient * bj, prime);
+            }
+        }
+        quotient[i] = coefficient;
+    }
+    remainder.truncate(degree);
+    (trim(quotient), trim(remainder))
+}
+
+/// Divides a by b modulo prime.
+///
+/// # Returns
+///
+/// The quotient and the remainder, which has lower degree than b.
+pub fn divrem(a: &[BigInt], b: &[BigInt], prime: &BigInt) -> (Vec<BigInt>, Vec<BigInt>) {
+    let b = trim(b.to_vec());
+    assert!(!b.is_empty(), "division by the zero polynomial");
+    let a = trim(a.to_vec());
+    if a.len() < b.len() {
+        return (Vec::new(), a);
+    }
+    if b.len() < NEWTON_CUTOFF {
+        return divrem_schoolbook(&a, &b, prime);
+    }
+
+    // rev(q) = rev(a) / rev(b) mod X^(deg a - deg b + 1)
+    let quotient_len = a.len() - b.len() + 1;
+    let a_rev: Vec<BigInt> = a.iter().rev().take(quotient_len).cloned().collect();
+    let b_rev: Vec<BigInt> = b.iter().rev().cloned().collect();
+    let mut quotient = mul(&a_rev, &inverse_series(&b_rev, quotient_len, prime), prime);
+    quotient.resize(quotient_len, BigInt::zero());
+    quotient.reverse();
+    let quotient = trim(quotient);
+
+    let mut remainder = sub(&a, &mul(&b, &quotient, prime), prime);
+    remainder.truncate(b.len() - 1);
+    (quotient, trim(remainder))
+}
+
+/// Evaluates a polynomial at x with Horner's rule.
+pub fn evaluate(a: &[BigInt], x: &BigInt, prime: &BigInt) -> BigInt {
+    a.iter()
+        .rev()
+        .fold(BigInt::zero(), |y, c| (y * x + c) % prime)
+}
+
+/// The products of (X - x_i) over ever larger groups of points.
+///
+/// `levels[0]` holds the linear factors and every node of `levels[k + 1]` is the product of two
+/// neighbouring nodes of `levels[k]`, or a copy of the last one if there is no neighbour.
+pub struct SubproductTree {
+    levels: Vec<Vec<Vec<BigInt>>>,
+}
+
+impl SubproductTree {
+    /// Builds the tree over the given points.
+    pub fn new(xs: &[BigInt], prime: &BigInt) -> Self {
+        assert!(!xs.is_empty(), "need at least one point");
+        let leaves: Vec<Vec<BigInt>> = xs
+            .iter()
+            .map(|x| vec![reduce(-x, prime), BigInt::one()])
+            .collect();
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => mul(left, right, prime),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        SubproductTree { levels }
+    }
+
+    /// The product of all linear factors, prod (X - x_i).
+    pub fn root(&self) -> &[BigInt] {
+        &self.levels.last().unwrap()[0]
+    }
+
+    /// Evaluates the polynomial at every point by reducing it down the tree.
+    pub fn evaluate(&self, a: &[BigInt], prime: &BigInt) -> Vec<BigInt> {
+        let mut remainders = vec![divrem(a, self.root(), prime).1];
+        for level in self.levels.iter().rev().skip(1) {
+            remainders = level
+                .iter()
+                .enumerate()
+                .map(|(i, node)| divrem(&remainders[i / 2], node, prime).1)
+                .collect();
+        }
+        // A remainder modulo X - x_i is the constant f(x_i)
+        remainders
+            .into_iter()
+            .map(|r| r.into_iter().next().unwrap_or_else(BigInt::zero))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::RandBigInt;
+
+    #[test]
+    fn fast_operations_match_schoolbook() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let mut rng = rand::thread_rng();
+        let mut random_poly = |length: usize| -> Vec<BigInt> {
+            (0..length)
+                .map(|_| rng.gen_bigint_range(&BigInt::zero(), &prime))
+                .collect()
+        };
+
+        let a = random_poly(150);
+        let b = random_poly(70);
+        let expected: Vec<BigInt> = mul_schoolbook(&a, &b)
+            .into_iter()
+            .map(|c| reduce(c, &prime))
+            .collect();
+        assert_eq!(mul(&a, &b, &prime), expected);
+
+        let (quotient, remainder) = divrem(&a, &b, &prime);
+        assert_eq!((quotient.clone(), remainder.clone()), divrem_schoolbook(&a, &b, &prime));
+        let mut recombined = mul(&b, &quotient, &prime);
+        add_into(&mut recombined, &remainder, 0);
+        let recombined: Vec<BigInt> = recombined.into_iter().map(|c| reduce(c, &prime)).collect();
+        assert_eq!(recombined, a);
+
+        let xs = random_poly(37);
+        let tree = SubproductTree::new(&xs, &prime);
+        let values: Vec<BigInt> = xs.iter().map(|x| evaluate(&a, x, &prime)).collect();
+        assert_eq!(tree.evaluate(&a, &prime), values);
+    }
+}
@@ -0,0 +1,216 @@
+//! Proactive share refresh and resharing.
+//!
+//! Refresh: every holder deals a fresh sharing of zero to the others and each holder adds the
+//! sub-shares it receives to its own share. The sum of the zero polynomials vanishes at x = 0, so
+//! the secret stays the same while every share changes, and old shares no longer combine with new
+//! ones.
+//!
+//! Resharing: a qualified set of old holders each deal a sharing of their own share under the new
+//! threshold to the new members. A new member weights the sub-shares with the old holders' Lagrange
+//! coefficients at 0, which interpolates a sharing of the secret without anyone computing it.
+
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+use crate::shamir::{
+    check_shares, create_shares, evaluate_polynomial, generate_coefficients,
+    lagrange_coefficients_at, modp_2048_prime, reconstruct_secret, ShamirError,
+};
+
+/// Deals a sharing of zero with the given threshold to the holders at `xs`.
+///
+/// # Arguments
+///
+/// * `threshold` - The threshold of the sharing being refreshed.
+/// * `xs` - The x-coordinates of all holders.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// One sub-share per holder, in the order of `xs`.
+pub fn deal_zero_sharing(threshold: usize, xs: &[BigInt], prime: &BigInt) -> Vec<BigInt> {
+    let coefficients = generate_coefficients(threshold, &BigInt::zero(), prime);
+    xs.iter()
+        .map(|x| evaluate_polynomial(x, &coefficients, prime))
+        .collect()
+}
+
+/// Adds the received sub-shares to a holder's share.
+///
+/// # Arguments
+///
+/// * `share` - The holder's current (x, y) share.
+/// * `received` - The sub-shares the holder received, one from every dealer.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The refreshed share at the same x.
+pub fn apply_refresh(share: &(BigInt, BigInt), received: &[BigInt], prime: &BigInt) -> (BigInt, BigInt) {
+    let (x, y) = share;
+    let y = received.iter().fold(y.clone(), |y, sub_share| (y + sub_share) % prime);
+    (x.clone(), y)
+}
+
+/// Runs one refresh round among all holders.
+///
+/// # Arguments
+///
+/// * `shares` - The current shares of all holders.
+/// * `threshold` - The threshold of the sharing.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The refreshed shares, in the same order.
+pub fn refresh(shares: &[(BigInt, BigInt)], threshold: usize, prime: &BigInt) -> Vec<(BigInt, BigInt)> {
+    let xs: Vec<BigInt> = shares.iter().map(|(x, _)| x.clone()).collect();
+    // dealings[i][j] is what holder i sends to holder j
+    let dealings: Vec<Vec<BigInt>> = shares
+        .iter()
+        .map(|_| deal_zero_sharing(threshold, &xs, prime))
+        .collect();
+    shares
+        .iter()
+        .enumerate()
+        .map(|(j, share)| {
+            let received: Vec<BigInt> = dealings.iter().map(|dealing| dealing[j].clone()).collect();
+            apply_refresh(share, &received, prime)
+        })
+        .collect()
+}
+
+/// Computes the Lagrange coefficients L_j(0) for the given x-coordinates.
+///
+/// # Arguments
+///
+/// * `xs` - Distinct, non-zero x-coordinates in [0, prime).
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// One coefficient per x, such that sum L_j(0) f(x_j) = f(0) for any f of degree < `xs.len()`,
+/// or an error if an x-coordinate is invalid.
+pub fn lagrange_coefficients(xs: &[BigInt], prime: &BigInt) -> Result<Vec<BigInt>, ShamirError> {
+    lagrange_coefficients_at(xs, &BigInt::zero(), prime)
+}
+
+/// Moves a sharing to a new threshold and a new set of holders.
+///
+/// # Arguments
+///
+/// * `old_shares` - Shares of at least the old threshold many old holders.
+/// * `old_threshold` - The threshold of the existing sharing.
+/// * `new_xs` - The x-coordinates of the new holders.
+/// * `new_threshold` - The threshold of the new sharing.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The new holders' shares, in the order of `new_xs`, or an error if there are too few old
+/// shares, the new threshold does not fit the new holders, or an x-coordinate is invalid.
+pub fn reshare(
+    old_shares: &[(BigInt, BigInt)],
+    old_threshold: usize,
+    new_xs: &[BigInt],
+    new_threshold: usize,
+    prime: &BigInt,
+) -> Result<Vec<(BigInt, BigInt)>, ShamirError> {
+    if old_threshold == 0 || old_shares.len() < old_threshold {
+        return Err(ShamirError::NotEnoughShares {
+            have: old_shares.len(),
+            need: old_threshold.max(1),
+        });
+    }
+    if new_threshold == 0 || new_threshold > new_xs.len() {
+        return Err(ShamirError::InvalidThreshold {
+            threshold: new_threshold,
+            num_shares: new_xs.len(),
+        });
+    }
+    let new_points: Vec<(BigInt, BigInt)> =
+        new_xs.iter().map(|x| (x.clone(), BigInt::zero())).collect();
+    check_shares(&new_points, prime)?;
+
+    // Exactly old_threshold dealers are needed, more would only add work
+    let dealers = &old_shares[..old_threshold];
+    let dealer_xs: Vec<BigInt> = dealers.iter().map(|(x, _)| x.clone()).collect();
+    let weights = lagrange_coefficients(&dealer_xs, prime)?;
+
+    // Each dealer shares its own y under the new threshold
+    let dealings: Vec<Vec<BigInt>> = dealers
+        .iter()
+        .map(|(_, y)| {
+            let coefficients = generate_coefficients(new_threshold, y, prime);
+            new_xs
+                .iter()
+                .map(|x| evaluate_polynomial(x, &coefficients, prime))
+                .collect()
+        })
+        .collect();
+
+    Ok(new_xs
+        .iter()
+        .enumerate()
+        .map(|(j, x)| {
+            let y = dealings
+                .iter()
+                .zip(&weights)
+                .fold(BigInt::zero(), |y, (dealing, weight)| (y + weight * &dealing[j]) % prime);
+            (x.clone(), y)
+        })
+        .collect())
+}
+
+/// Refreshes a 3-of-5 sharing, then reshares it to 4-of-7 among partly new holders.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let secret = BigInt::from(123456789);
+    let shares = create_shares(5, 3, &secret, &prime);
+
+    let refreshed = refresh(&shares, 3, &prime);
+    println!(
+        "Every share changed: {}",
+        shares.iter().zip(&refreshed).all(|(old, new)| old.1 != new.1)
+    );
+    println!(
+        "Refreshed shares 1-3 give: {}",
+        reconstruct_secret(&refreshed[..3], &prime).unwrap()
+    );
+    // An attacker who stole share 1 before and shares 2-3 after the refresh learns nothing
+    let mixed = [shares[0].clone(), refreshed[1].clone(), refreshed[2].clone()];
+    println!("Old share 1 with new shares 2-3 gives: {}", reconstruct_secret(&mixed, &prime).unwrap());
+
+    // Holders 4 and 5 leave, 6 to 9 join, and the threshold goes up to 4
+    let new_xs: Vec<BigInt> = [1, 2, 3, 6, 7, 8, 9].iter().map(|&x| BigInt::from(x)).collect();
+    let reshared = reshare(&refreshed[1..4], 3, &new_xs, 4, &prime).unwrap();
+    println!(
+        "4 of the reshared shares give: {}",
+        reconstruct_secret(&reshared[3..], &prime).unwrap()
+    );
+    println!(
+        "3 of the reshared shares give: {}",
+        reconstruct_secret(&reshared[..3], &prime).unwrap()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refresh_and_reshare_keep_the_secret() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let secret = BigInt::from(31337);
+        let shares = create_shares(4, 2, &secret, &prime);
+
+        let refreshed = refresh(&shares, 2, &prime);
+        assert_eq!(reconstruct_secret(&refreshed[2..], &prime), Ok(secret.clone()));
+        assert_ne!(refreshed, shares);
+
+        let new_xs: Vec<BigInt> = (10..15).map(BigInt::from).collect();
+        let reshared = reshare(&refreshed[1..], 2, &new_xs, 3, &prime).unwrap();
+        assert_eq!(reconstruct_secret(&reshared[..3], &prime), Ok(secret.clone()));
+        assert_eq!(reconstruct_secret(&reshared[2..], &prime), Ok(secret));
+    }
+}
@@ -0,0 +1,326 @@
+//! Error-correcting reconstruction with Berlekamp-Welch decoding.
+//!
+//! The shares of a degree t - 1 polynomial are a Reed-Solomon codeword, so with n shares up to
+//! e = (n - t) / 2 of them can be wrong and the polynomial is still unique. Berlekamp-Welch finds
+//! a monic error locator E of degree e and Q = P * E of degree < e + t with Q(x_i) = y_i E(x_i)
+//! for every share, which is a linear system. Then P = Q / E, and the faulty shares are the ones
+//! where P(x_i) != y_i.
+
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+
+use crate::shamir::{
+    check_shares, create_shares, evaluate_polynomial, modinv, modp_2048_prime, reconstruct_secret,
+    ShamirError,
+};
+
+/// The secret together with the x-coordinates of the shares that were corrected.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Decoded {
+    pub secret: BigInt,
+    pub faulty: Vec<BigInt>,
+}
+
+/// Why robust reconstruction failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    NotEnoughShares {
+        have: usize,
+        need: usize,
+    },
+    /// More shares are wrong than the redundancy can correct.
+    TooManyErrors {
+        correctable: usize,
+    },
+    /// The threshold is zero.
+    InvalidThreshold {
+        threshold: usize,
+    },
+    /// A share is malformed, e.g. its x-coordinate is zero or repeated.
+    InvalidShares(ShamirError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::NotEnoughShares { have, need } => {
+                write!(f, "{} shares given but {} are needed", have, need)
+            }
+            DecodeError::TooManyErrors { correctable } => write!(
+                f,
+                "more than {} shares are faulty, the secret can't be recovered",
+                correctable
+            ),
+            DecodeError::InvalidThreshold { threshold } => {
+                write!(
+                    f,
+                    "threshold {} is invalid, it must be at least 1",
+                    threshold
+                )
+            }
+            DecodeError::InvalidShares(error) => write!(f, "invalid shares: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Reduces a value into [0, prime).
+fn reduce(value: BigInt, prime: &BigInt) -> BigInt {
+    ((value % prime) + prime) % prime
+}
+
+/// Solves the linear system `matrix * unknowns = rhs` modulo prime by Gauss-Jordan elimination.
+/// Free unknowns are set to zero.
+///
+/// # Returns
+///
+/// A solution, or `None` if the system is inconsistent.
+fn solve_linear_system(
+    matrix: Vec<Vec<BigInt>>,
+    rhs: Vec<BigInt>,
+    prime: &BigInt,
+) -> Option<Vec<BigInt>> {
+    gauss_jordan(matrix, rhs, prime).map(|(solution, _)| solution)
+}
+
+/// Solves the linear system like `solve_linear_system`, but only if the solution is unique.
+///
+/// # Returns
+///
+/// The solution, or `None` if the system is inconsistent or has free unknowns.
+pub fn solve_unique(
+    matrix: Vec<Vec<BigInt>>,
+    rhs: Vec<BigInt>,
+    prime: &BigInt,
+) -> Option<Vec<BigInt>> {
+    let columns = matrix.first().map_or(0, Vec::len);
+    gauss_jordan(matrix, rhs, prime)
+        .filter(|(_, rank)| *rank == columns)
+        .map(|(solution, _)| solution)
+}
+
+/// Gauss-Jordan elimination shared by the solvers.
+///
+/// # Returns
+///
+/// A solution with free unknowns set to zero and the rank of the matrix, or `None` if the system
+/// is inconsistent.
+fn gauss_jordan(
+    mut matrix: Vec<Vec<BigInt>>,
+    mut rhs: Vec<BigInt>,
+    prime: &BigInt,
+) -> Option<(Vec<BigInt>, usize)> {
+    let rows = matrix.len();
+    let columns = matrix.first().map_or(0, Vec::len);
+    let mut pivots = Vec::new();
+
+    let mut row = 0;
+    for column in 0..columns {
+        let Some(pivot) = (row..rows).find(|&r| !matrix[r][column].is_zero()) else {
+            continue;
+        };
+        matrix.swap(row, pivot);
+        rhs.swap(row, pivot);
+
+        // Scale the pivot row so the pivot is 1, then clear the column everywhere else
+        let inverse = modinv(&matrix[row][column], prime).unwrap();
+        for entry in matrix[row].iter_mut() {
+            *entry = (&*entry * &inverse) % prime;
+        }
+        rhs[row] = (&rhs[row] * &inverse) % prime;
+        let pivot_row = matrix[row].clone();
+        for other in 0..rows {
+            if other != row && !matrix[other][column].is_zero() {
+                let factor = matrix[other][column].clone();
+                for (entry, pivot_entry) in matrix[other].iter_mut().zip(&pivot_row) {
+                    *entry = reduce(&*entry - &factor * pivot_entry, prime);
+                }
+                rhs[other] = reduce(&rhs[other] - &factor * &rhs[row], prime);
+            }
+        }
+        pivots.push(column);
+        row += 1;
+        if row == rows {
+            break;
+        }
+    }
+
+    // Rows without a pivot read 0 = rhs
+    if rhs[row..].iter().any(|value| !value.is_zero()) {
+        return None;
+    }
+    let mut solution = vec![BigInt::zero(); columns];
+    for (r, &column) in pivots.iter().enumerate() {
+        solution[column] = rhs[r].clone();
+    }
+    Some((solution, pivots.len()))
+}
+
+/// Divides the numerator by a monic denominator, coefficients lowest degree first.
+///
+/// # Returns
+///
+/// The quotient and remainder.
+fn divide_by_monic(
+    numerator: &[BigInt],
+    denominator: &[BigInt],
+    prime: &BigInt,
+) -> (Vec<BigInt>, Vec<BigInt>) {
+    let degree = denominator.len() - 1;
+    let mut remainder = numerator.to_vec();
+    if remainder.len() <= degree {
+        return (Vec::new(), remainder);
+    }
+    let mut quotient = vec![BigInt::zero(); remainder.len() - degree];
+    for i in (0..quotient.len()).rev() {
+        let coefficient = remainder[i + degree].clone();
+        for (j, d) in denominator.iter().enumerate() {
+            remainder[i + j] = reduce(&remainder[i + j] - &coefficient * d, prime);
+        }
+        quotient[i] = coefficient;
+    }
+    remainder.truncate(degree);
+    (quotient, remainder)
+}
+
+/// Reconstructs the secret while correcting up to (n - t) / 2 faulty shares.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of tuples containing x and y coordinates, at least `threshold` of them.
+/// * `threshold` - The number of shares the secret was split for.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The secret and the x-coordinates of the faulty shares, or an error if the threshold is zero,
+/// the shares fail `check_shares`, or too many are faulty.
+pub fn robust_reconstruct(
+    shares: &[(BigInt, BigInt)],
+    threshold: usize,
+    prime: &BigInt,
+) -> Result<Decoded, DecodeError> {
+    if threshold == 0 {
+        return Err(DecodeError::InvalidThreshold { threshold });
+    }
+    let n = shares.len();
+    if n < threshold {
+        return Err(DecodeError::NotEnoughShares {
+            have: n,
+            need: threshold,
+        });
+    }
+    check_shares(shares, prime).map_err(DecodeError::InvalidShares)?;
+    let correctable = (n - threshold) / 2;
+
+    // Unknowns: q_0..q_{e+t-1}, then e_0..e_{e-1} (E is monic, so e_e = 1)
+    // Row i: sum q_j x^j - y sum e_j x^j = y x^e
+    let q_len = correctable + threshold;
+    let mut matrix = Vec::with_capacity(n);
+    let mut rhs = Vec::with_capacity(n);
+    for (x, y) in shares {
+        let x = reduce(x.clone(), prime);
+        let y = reduce(y.clone(), prime);
+        let powers: Vec<BigInt> =
+            std::iter::successors(Some(BigInt::one()), |p| Some((p * &x) % prime))
+                .take(q_len)
+                .collect();
+        let mut row: Vec<BigInt> = powers.clone();
+        row.extend(
+            powers[..correctable]
+                .iter()
+                .map(|power| reduce(-(&y * power), prime)),
+        );
+        matrix.push(row);
+        rhs.push((&y * &powers[correctable]) % prime);
+    }
+
+    let too_many = DecodeError::TooManyErrors { correctable };
+    let solution = solve_linear_system(matrix, rhs, prime).ok_or_else(|| too_many.clone())?;
+    let q = &solution[..q_len];
+    let mut locator = solution[q_len..].to_vec();
+    locator.push(BigInt::one());
+
+    let (polynomial, remainder) = divide_by_monic(q, &locator, prime);
+    if remainder.iter().any(|r| !r.is_zero()) {
+        return Err(too_many);
+    }
+
+    let faulty: Vec<BigInt> = shares
+        .iter()
+        .filter(|(x, y)| evaluate_polynomial(x, &polynomial, prime) != reduce(y.clone(), prime))
+        .map(|(x, _)| x.clone())
+        .collect();
+    if faulty.len() > correctable {
+        return Err(too_many);
+    }
+
+    Ok(Decoded {
+        secret: polynomial.first().cloned().unwrap_or_else(BigInt::zero),
+        faulty,
+    })
+}
+
+/// Corrupts two of seven 3-of-7 shares and recovers the secret anyway.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let secret = BigInt::from(123456789);
+    let mut shares = create_shares(7, 3, &secret, &prime);
+    shares[1].1 += 1;
+    shares[5].1 = BigInt::from(42);
+
+    println!(
+        "Plain interpolation of the first 3 shares matches: {}",
+        reconstruct_secret(&shares[..3], &prime) == Ok(secret.clone())
+    );
+    match robust_reconstruct(&shares, 3, &prime) {
+        Ok(decoded) => println!(
+            "Robust reconstruction recovered {} and flagged shares at x = {:?}",
+            decoded.secret, decoded.faulty
+        ),
+        Err(error) => println!("Robust reconstruction failed: {}", error),
+    }
+
+    shares[6].1 += 1;
+    if let Err(error) = robust_reconstruct(&shares, 3, &prime) {
+        println!("With a third faulty share: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrects_up_to_half_the_redundancy() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let secret = BigInt::from(987654321);
+        let mut shares = create_shares(9, 4, &secret, &prime);
+
+        let clean = robust_reconstruct(&shares, 4, &prime).unwrap();
+        assert_eq!(clean.secret, secret);
+        assert!(clean.faulty.is_empty());
+
+        shares[0].1 += 5;
+        shares[7].1 = BigInt::zero();
+        let decoded = robust_reconstruct(&shares, 4, &prime).unwrap();
+        assert_eq!(decoded.secret, secret);
+        assert_eq!(decoded.faulty, vec![BigInt::from(1), BigInt::from(8)]);
+
+        shares[3].1 += 1;
+        assert!(robust_reconstruct(&shares, 4, &prime).is_err());
+
+        assert_eq!(
+            robust_reconstruct(&shares, 0, &prime),
+            Err(DecodeError::InvalidThreshold { threshold: 0 })
+        );
+        shares[2].0 = BigInt::zero();
+        assert_eq!(
+            robust_reconstruct(&shares, 4, &prime),
+            Err(DecodeError::InvalidShares(ShamirError::ZeroIndex))
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Shamir sharing over a chosen prime field with caller-chosen x-coordinates.
+//!
+//! `create_shares` always shares modulo the MODP-2048 prime at x = 1..=n. In protocols where the
+//! x-coordinate is a participant ID, the shares have to sit at given points instead, and smaller
+//! fields like the P-256 group order keep the shares short. `ShamirScheme` fixes the field and
+//! the threshold once and checks every x-coordinate: zero would hand out the secret itself and a
+//! repeated x makes interpolation impossible.
+
+use std::collections::HashSet;
+
+use num_bigint::{BigInt, RandBigInt, Sign};
+use num_traits::{One, Zero};
+use rand::thread_rng;
+
+use crate::lagrange::fast_reconstruct_secret;
+use crate::poly::reduce;
+pub use crate::shamir::ShamirError;
+use crate::shamir::{evaluate_polynomial, generate_coefficients, modp_2048_prime};
+
+/// Number of Miller-Rabin rounds for a custom prime, for an error probability below 2^-128.
+const MILLER_RABIN_ROUNDS: usize = 64;
+
+/// The prime fields with a name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamedPrime {
+    /// The order of the P-256 curve group, so shares can be used as P-256 scalars.
+    P256Order,
+    /// The Mersenne prime 2^127 - 1.
+    Mersenne127,
+    /// The Mersenne prime 2^521 - 1.
+    Mersenne521,
+    /// The 2048-bit MODP prime from RFC 3526, the field of `create_shares`.
+    Modp2048,
+}
+
+impl NamedPrime {
+    /// Looks a prime up by the name used on the command line.
+    pub fn from_name(name: &str) -> Option<NamedPrime> {
+        match name {
+            "p256" => Some(NamedPrime::P256Order),
+            "m127" => Some(NamedPrime::Mersenne127),
+            "m521" => Some(NamedPrime::Mersenne521),
+            "modp2048" => Some(NamedPrime::Modp2048),
+            _ => None,
+        }
+    }
+
+    /// The prime itself.
+    pub fn prime(self) -> BigInt {
+        match self {
+            NamedPrime::P256Order => BigInt::parse_bytes(
+                b"FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+                16,
+            )
+            .unwrap(),
+            NamedPrime::Mersenne127 => (BigInt::one() << 127) - 1,
+            NamedPrime::Mersenne521 => (BigInt::one() << 521) - 1,
+            NamedPrime::Modp2048 => modp_2048_prime(),
+        }
+    }
+}
+
+/// Miller-Rabin with random bases.
+///
+/// # Arguments
+///
+/// * `n` - The number to test.
+/// * `rounds` - The number of random bases to try.
+///
+/// # Returns
+///
+/// `false` if `n` is certainly composite, `true` if it is prime with error probability at most
+/// 4^-rounds.
+pub fn is_probable_prime(n: &BigInt, rounds: usize) -> bool {
+    let two = BigInt::from(2);
+    if *n < two {
+        return false;
+    }
+    for small in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if *n == BigInt::from(small) {
+            return true;
+        }
+        if (n % small).is_zero() {
+            return false;
+        }
+    }
+
+    // n - 1 = d * 2^s with d odd
+    let n_minus_one: BigInt = n - 1;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+    let mut rng = thread_rng();
+    'bases: for _ in 0..rounds {
+        let a = rng.gen_bigint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Shamir secret sharing with a fixed field and threshold.
+#[derive(Clone, Debug)]
+pub struct ShamirScheme {
+    prime: BigInt,
+    threshold: usize,
+}
+
+impl ShamirScheme {
+    /// Creates a scheme over one of the named fields.
+    ///
+    /// The named moduli are known primes, so unlike `with_prime` this skips the primality test.
+    pub fn new(prime: NamedPrime, threshold: usize) -> Result<Self, ShamirError> {
+        Self::checked(prime.prime(), threshold)
+    }
+
+    /// Creates a scheme over a custom prime field.
+    ///
+    /// # Arguments
+    ///
+    /// * `prime` - The modulus, checked with Miller-Rabin.
+    /// * `threshold` - The minimum number of shares required to reconstruct the secret.
+    ///
+    /// # Returns
+    ///
+    /// The scheme, or an error if the modulus is not prime or the threshold is zero.
+    pub fn with_prime(prime: BigInt, threshold: usize) -> Result<Self, ShamirError> {
+        if !is_probable_prime(&prime, MILLER_RABIN_ROUNDS) {
+            return Err(ShamirError::NotPrime);
+        }
+        Self::checked(prime, threshold)
+    }
+
+    /// Creates a scheme over a modulus already known to be prime, checking only the threshold.
+    fn checked(prime: BigInt, threshold: usize) -> Result<Self, ShamirError> {
+        if threshold == 0 {
+            return Err(ShamirError::InvalidThreshold {
+                threshold,
+                num_shares: 0,
+            });
+        }
+        Ok(ShamirScheme { prime, threshold })
+    }
+
+    /// The modulus of the field.
+    pub fn prime(&self) -> &BigInt {
+        &self.prime
+    }
+
+    /// The minimum number of shares required to reconstruct the secret.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Reduces the x-coordinates into the field and rejects zero and repeated values.
+    fn check_indices(&self, xs: &[BigInt]) -> Result<Vec<BigInt>, ShamirError> {
+        let mut seen = HashSet::with_capacity(xs.len());
+        xs.iter()
+            .map(|x| {
+                let x = reduce(x.clone(), &self.prime);
+                if x.is_zero() {
+                    Err(ShamirError::ZeroIndex)
+                } else if !seen.insert(x.clone()) {
+                    Err(ShamirError::DuplicateIndex(x))
+                } else {
+                    Ok(x)
+                }
+            })
+            .collect()
+    }
+
+    /// Creates one share per given x-coordinate, e.g. per participant ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret to be shared, in [0, prime).
+    /// * `xs` - The x-coordinates, non-zero and distinct modulo the prime.
+    ///
+    /// # Returns
+    ///
+    /// A vector of tuples, each containing an x and y coordinate, with x reduced into the field.
+    pub fn split_at(
+        &self,
+        secret: &BigInt,
+        xs: &[BigInt],
+    ) -> Result<Vec<(BigInt, BigInt)>, ShamirError> {
+        if secret.sign() == Sign::Minus || *secret >= self.prime {
+            return Err(ShamirError::SecretOutOfRange);
+        }
+        if self.threshold > xs.len() {
+            return Err(ShamirError::InvalidThreshold {
+                threshold: self.threshold,
+                num_shares: xs.len(),
+            });
+        }
+        let xs = self.check_indices(xs)?;
+        let coefficients = generate_coefficients(self.threshold, secret, &self.prime);
+        Ok(xs
+            .into_iter()
+            .map(|x| {
+                let y = evaluate_polynomial(&x, &coefficients, &self.prime);
+                (x, y)
+            })
+            .collect())
+    }
+
+    /// Creates shares at uniformly random, distinct, non-zero x-coordinates.
+    ///
+    /// Random points hide how many shares were dealt and which one is which; over small fields
+    /// they are drawn again until they are distinct.
+    pub fn split_random(
+        &self,
+        secret: &BigInt,
+        num_shares: usize,
+    ) -> Result<Vec<(BigInt, BigInt)>, ShamirError> {
+        if BigInt::from(num_shares) >= self.prime {
+            return Err(ShamirError::TooManyShares(num_shares));
+        }
+        let mut rng = thread_rng();
+        let mut seen = HashSet::with_capacity(num_shares);
+        let mut xs = Vec::with_capacity(num_shares);
+        while xs.len() < num_shares {
+            let x = rng.gen_bigint_range(&BigInt::one(), &self.prime);
+            if seen.insert(x.clone()) {
+                xs.push(x);
+            }
+        }
+        self.split_at(secret, &xs)
+    }
+
+    /// Reconstructs the secret from at least `threshold` shares at any valid x-coordinates.
+    ///
+    /// # Arguments
+    ///
+    /// * `shares` - A slice of tuples containing x and y coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The secret, or an error if there are too few shares, an x-coordinate is zero or repeated, or
+    /// a value is outside the field.
+    pub fn reconstruct(&self, shares: &[(BigInt, BigInt)]) -> Result<BigInt, ShamirError> {
+        if shares.len() < self.threshold {
+            return Err(ShamirError::NotEnoughShares {
+                have: shares.len(),
+                need: self.threshold,
+            });
+        }
+        fast_reconstruct_secret(shares, &self.prime)
+    }
+}
+
+/// Shares a secret among participants identified by their IDs over each named field.
+pub fn run_demo() {
+    let secret = BigInt::from(987654321);
+    let participant_ids: Vec<BigInt> = [1001, 2002, 3003, 4004, 5005]
+        .into_iter()
+        .map(BigInt::from)
+        .collect();
+
+    for named in [
+        NamedPrime::P256Order,
+        NamedPrime::Mersenne127,
+        NamedPrime::Mersenne521,
+    ] {
+        let scheme = ShamirScheme::new(named, 3).unwrap();
+        let shares = scheme.split_at(&secret, &participant_ids).unwrap();
+        println!(
+            "{:?} ({} bits): shares of IDs 2002, 4004 and 5005 reconstruct {}",
+            named,
+            scheme.prime().bits(),
+            scheme
+                .reconstruct(&[shares[1].clone(), shares[3].clone(), shares[4].clone()])
+                .unwrap()
+        );
+    }
+
+    let scheme = ShamirScheme::new(NamedPrime::Mersenne127, 3).unwrap();
+    let shares = scheme.split_random(&secret, 4).unwrap();
+    println!(
+        "Random x-coordinates {:?} reconstruct {}",
+        shares.iter().map(|(x, _)| x).collect::<Vec<_>>(),
+        scheme.reconstruct(&shares[1..]).unwrap()
+    );
+
+    let ids = [BigInt::from(7), BigInt::zero(), BigInt::from(9)];
+    println!(
+        "Participant ID 0: {}",
+        scheme.split_at(&secret, &ids).unwrap_err()
+    );
+    let ids = [
+        BigInt::from(7),
+        BigInt::from(8),
+        &BigInt::from(7) + scheme.prime(),
+    ];
+    println!(
+        "IDs 7, 8 and 7 + p: {}",
+        scheme.split_at(&secret, &ids).unwrap_err()
+    );
+    println!(
+        "Custom modulus 2^127 + 1: {}",
+        ShamirScheme::with_prime((BigInt::one() << 127) + 1, 3).unwrap_err()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_at_participant_ids() {
+        let secret = BigInt::from(31337);
+        let scheme = ShamirScheme::new(NamedPrime::P256Order, 3).unwrap();
+        let ids: Vec<BigInt> = [17, 4, 99, 1234].into_iter().map(BigInt::from).collect();
+        let shares = scheme.split_at(&secret, &ids).unwrap();
+        assert_eq!(
+            shares.iter().map(|(x, _)| x).collect::<Vec<_>>(),
+            ids.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(scheme.reconstruct(&shares[1..]), Ok(secret.clone()));
+        assert_eq!(
+            scheme.reconstruct(&shares[..2]),
+            Err(ShamirError::NotEnoughShares { have: 2, need: 3 })
+        );
+
+        let zero_mod_p = vec![BigInt::from(1), BigInt::from(2), scheme.prime().clone()];
+        assert_eq!(
+            scheme.split_at(&secret, &zero_mod_p),
+            Err(ShamirError::ZeroIndex)
+        );
+        let duplicate = vec![BigInt::from(5), BigInt::from(6), BigInt::from(5)];
+        assert_eq!(
+            scheme.split_at(&secret, &duplicate),
+            Err(ShamirError::DuplicateIndex(BigInt::from(5)))
+        );
+
+        let scheme = ShamirScheme::with_prime(BigInt::from(65537), 2).unwrap();
+        let shares = scheme.split_random(&BigInt::from(1000), 3).unwrap();
+        assert_eq!(scheme.reconstruct(&shares[1..]), Ok(BigInt::from(1000)));
+        assert_eq!(
+            scheme.split_random(&BigInt::from(1000), 65537),
+            Err(ShamirError::TooManyShares(65537))
+        );
+        let mut foreign = shares.clone();
+        foreign[0].1 = BigInt::from(70000);
+        assert_eq!(
+            scheme.reconstruct(&foreign),
+            Err(ShamirError::ValueOutOfRange(BigInt::from(70000)))
+        );
+        assert_eq!(
+            ShamirScheme::with_prime(BigInt::from(65535), 2).unwrap_err(),
+            ShamirError::NotPrime
+        );
+        assert!(is_probable_prime(&NamedPrime::Mersenne521.prime(), 8));
+        assert!(!is_probable_prime(
+            &(NamedPrime::Mersenne127.prime() + 2),
+            8
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+/*
+ * This code was nearly one-shotted by Chat-GPT o1
+ * 
+ *     _.-^^---....,,--       
+ * _--                  --_  
+ * <                        >)
+ * |                         | 
+ * \._                   _./  
+ *    ```--. . , ; .--'''       
+ *          | |   |             
+ *       .-=||  | |=-.   
+ *       `-=#$%&%$#=-'   
+ *          | ;  :|     
+ *       _.-"O.o"O"-._
+ *      /_.-'    '-._ \
+ *     /    _     _   \
+ *    /|  /         \ |\
+ *   | | |_o      o_| | |
+ *   | |     \___/     | |
+ *   | \    ~~   ~~   / |
+ *    \_\    (   )   /_/
+ *       \__________/
+ *         |_...._|
+ *         \______/
+ * 
+ */
+use std::collections::HashSet;
+use std::fmt;
+
+use num_bigint::{BigInt, RandBigInt, Sign, ToBigInt};
+use num_traits::{Zero, One};
+use rand::{thread_rng, Rng};
+
+/// Why sharing or reconstruction failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShamirError {
+    /// A custom modulus failed the primality test.
+    NotPrime,
+    /// The threshold is zero or larger than the number of shares.
+    InvalidThreshold {
+        threshold: usize,
+        num_shares: usize,
+    },
+    /// More shares were requested than the field has non-zero x-coordinates.
+    TooManyShares(usize),
+    /// The secret is negative or not smaller than the prime.
+    SecretOutOfRange,
+    /// An x-coordinate is zero modulo the prime; that share would be the secret.
+    ZeroIndex,
+    /// Two shares have the same x-coordinate modulo the prime.
+    DuplicateIndex(BigInt),
+    NotEnoughShares {
+        have: usize,
+        need: usize,
+    },
+    /// A share coordinate is not in [0, prime), so it belongs to a different field.
+    ValueOutOfRange(BigInt),
+    /// The number of values does not match the parameters, e.g. y-values for precomputed
+    /// weights or byte shares of different lengths.
+    ParameterMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// The shares do not form an authorized set of the access structure.
+    NotAuthorized,
+    /// Shares combined by a homomorphic operation belong to different holders.
+    IndexMismatch {
+        expected: BigInt,
+        found: BigInt,
+    },
+    /// A share's x-coordinate is not among the helpers it is meant to be combined with.
+    UnknownHelper(BigInt),
+    /// The shares are authorized but their interpolation system is singular, e.g. because
+    /// hierarchical IDs were not assigned in level order.
+    SingularSystem,
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShamirError::NotPrime => write!(f, "the modulus is not prime"),
+            ShamirError::InvalidThreshold {
+                threshold,
+                num_shares,
+            } => write!(
+                f,
+                "threshold {} is not between 1 and the {} shares",
+                threshold, num_shares
+            ),
+            ShamirError::TooManyShares(num_shares) => {
+                write!(f, "{} shares need more x-coordinates than the field has", num_shares)
+            }
+            ShamirError::SecretOutOfRange => write!(f, "the secret is not in [0, prime)"),
+            ShamirError::ZeroIndex => write!(f, "a share has x-coordinate zero"),
+            ShamirError::DuplicateIndex(x) => write!(f, "more than one share has x = {}", x),
+            ShamirError::NotEnoughShares { have, need } => {
+                write!(f, "{} shares given but {} are needed", have, need)
+            }
+            ShamirError::ValueOutOfRange(value) => {
+                write!(f, "share value {} is outside the field", value)
+            }
+            ShamirError::ParameterMismatch { expected, found } => {
+                write!(f, "expected {} values but found {}", expected, found)
+            }
+            ShamirError::IndexMismatch { expected, found } => {
+                write!(f, "shares at x = {} and x = {} can't be combined", expected, found)
+            }
+            ShamirError::NotAuthorized => write!(f, "the shares are not an authorized set"),
+            ShamirError::UnknownHelper(x) => write!(f, "x = {} is not one of the helpers", x),
+            ShamirError::SingularSystem => {
+                write!(f, "the shares do not determine a unique polynomial")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShamirError {}
+
+/// Returns the 2048-bit MODP prime from RFC 3526 (group 14), the default field for sharing.
+pub fn modp_2048_prime() -> BigInt {
+    BigInt::parse_bytes(
+        b"FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+        16,
+    )
+    .unwrap()
+}
+
+/// Generates random polynomial coefficients for the secret sharing.
+/// The first coefficient is the secret itself.
+///
+/// # Arguments
+///
+/// * `threshold` - The minimum number of shares required to reconstruct the secret.
+/// * `secret` - The secret to be shared.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// A vector of `BigInt` coefficients for the polynomial.
+pub fn generate_coefficients(threshold: usize, secret: &BigInt, prime: &BigInt) -> Vec<BigInt> {
+    generate_coefficients_with_rng(threshold, secret, prime, &mut thread_rng())
+}
+
+/// Generates the polynomial coefficients like `generate_coefficients`, drawing from the given
+/// random number generator so that a seeded run can be repeated.
+pub fn generate_coefficients_with_rng<R: Rng + ?Sized>(
+    threshold: usize,
+    secret: &BigInt,
+    prime: &BigInt,
+    rng: &mut R,
+) -> Vec<BigInt> {
+    let mut coefficients = Vec::with_capacity(threshold);
+    coefficients.push(secret.clone()); // a_0 = secret
+    for _ in 1..threshold {
+        let coeff = rng.gen_bigint_range(&BigInt::zero(), prime);
+        coefficients.push(coeff);
+    }
+    coefficients
+}
+
+/// Evaluates the polynomial at a given x value.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate at which to evaluate the polynomial.
+/// * `coefficients` - The coefficients of the polynomial.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The y-coordinate corresponding to the given x.
+pub fn evaluate_polynomial(x: &BigInt, coefficients: &[BigInt], prime: &BigInt) -> BigInt {
+    let mut y = BigInt::zero();      // Initialize the result y to zero
+    let mut x_pow = BigInt::one();   // Initialize x_pow to x^0 = 1
+    for coeff in coefficients {
+        // Compute the current term of the polynomial: coeff * x_pow mod prime
+        let term = (coeff * &x_pow) % prime;
+        // Add the current term to the result y modulo prime
+        y = (y + term) % prime;
+        // Update x_pow for the next term: x_pow = x_pow * x mod prime
+        x_pow = (x_pow * x) % prime;
+    }
+    y   // Return the evaluated polynomial value y = f(x) mod prime
+}
+
+
+/// Creates shares for the secret.
+///
+/// # Arguments
+///
+/// * `num_shares` - The total number of shares to create.
+/// * `threshold` - The minimum number of shares required to reconstruct the secret.
+/// * `secret` - The secret to be shared.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// A vector of tuples, each containing an x and y coordinate.
+pub fn create_shares(
+    num_shares: usize,
+    threshold: usize,
+    secret: &BigInt,
+    prime: &BigInt,
+) -> Vec<(BigInt, BigInt)> {
+    let coefficients = generate_coefficients(threshold, secret, prime);
+    let mut shares = Vec::with_capacity(num_shares);
+    for i in 1..=num_shares {
+        let x = i.to_bigint().unwrap();
+        let y = evaluate_polynomial(&x, &coefficients, prime);
+        shares.push((x, y));
+    }
+    shares
+}
+
+/// Computes the modular inverse of a number.
+///
+/// # Arguments
+///
+/// * `a` - The number to find the inverse of.
+/// * `m` - The modulus.
+///
+/// # Returns
+///
+/// An `Option` containing the modular inverse if it exists.
+pub fn modinv(a: &BigInt, m: &BigInt) -> Option<BigInt> {
+    a.modinv(m)
+}
+
+/// Checks that shares can be interpolated: at least one share, every coordinate in [0, prime),
+/// and distinct non-zero x-coordinates.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of tuples containing x and y coordinates.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The first problem found, if any.
+pub fn check_shares(shares: &[(BigInt, BigInt)], prime: &BigInt) -> Result<(), ShamirError> {
+    if shares.is_empty() {
+        return Err(ShamirError::NotEnoughShares { have: 0, need: 1 });
+    }
+    let mut seen = HashSet::with_capacity(shares.len());
+    for (x, y) in shares {
+        for value in [x, y] {
+            if value.sign() == Sign::Minus || value >= prime {
+                return Err(ShamirError::ValueOutOfRange(value.clone()));
+            }
+        }
+        if x.is_zero() {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if !seen.insert(x) {
+            return Err(ShamirError::DuplicateIndex(x.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Reconstructs the secret from a given set of shares using Lagrange interpolation.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of tuples containing x and y coordinates.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The reconstructed secret as a `BigInt`, or an error if the shares fail `check_shares` or the
+/// modulus is not prime.
+pub fn reconstruct_secret(shares: &[(BigInt, BigInt)], prime: &BigInt) -> Result<BigInt, ShamirError> {
+    check_shares(shares, prime)?;
+    let mut secret = BigInt::zero(); // Initialize the reconstructed secret to zero
+    // Iterate over each share (xj, yj)
+    for (j, (xj, yj)) in shares.iter().enumerate() {
+        let mut numerator = BigInt::one();    // Initialize numerator for Lagrange basis polynomial
+        let mut denominator = BigInt::one();  // Initialize denominator for Lagrange basis polynomial
+        // Compute the Lagrange basis polynomial L_j(0)
+        for (m, (xm, _)) in shares.iter().enumerate() {
+            if m != j {
+                // Update numerator: multiply by (-xm) mod prime
+                numerator = (numerator * -xm) % prime;
+                // Update denominator: multiply by (xj - xm) mod prime
+                denominator = (denominator * (xj - xm)) % prime;
+            }
+        }
+        // Distinct x's give a non-zero denominator, so only a composite modulus has no inverse
+        let inv = modinv(&denominator, prime).ok_or(ShamirError::NotPrime)?;
+        // Compute the term to be added: yj * numerator * inv mod prime
+        let term = yj * numerator * inv;
+        // Add the term to the secret modulo prime
+        secret = (secret + term) % prime;
+    }
+    Ok((secret + prime) % prime)  // Ensure the secret is positive by adding prime if necessary
+}
+
+/// Computes the Lagrange coefficients L_j(x) for the given x-coordinates.
+///
+/// # Arguments
+///
+/// * `xs` - Distinct, non-zero x-coordinates in [0, prime).
+/// * `x` - The point to interpolate at.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// One coefficient per x_j, such that sum L_j(x) f(x_j) = f(x) for any f of degree < `xs.len()`,
+/// or an error if an x-coordinate is invalid.
+pub fn lagrange_coefficients_at(
+    xs: &[BigInt],
+    x: &BigInt,
+    prime: &BigInt,
+) -> Result<Vec<BigInt>, ShamirError> {
+    let points: Vec<(BigInt, BigInt)> = xs.iter().map(|xj| (xj.clone(), BigInt::zero())).collect();
+    check_shares(&points, prime)?;
+    xs.iter()
+        .enumerate()
+        .map(|(j, xj)| {
+            let mut numerator = BigInt::one();
+            let mut denominator = BigInt::one();
+            for (m, xm) in xs.iter().enumerate() {
+                if m != j {
+                    numerator = (numerator * (x - xm)) % prime;
+                    denominator = (denominator * (xj - xm)) % prime;
+                }
+            }
+            let inverse = modinv(&denominator, prime).ok_or(ShamirError::NotPrime)?;
+            Ok(((numerator * inverse) % prime + prime) % prime)
+        })
+        .collect()
+}
+
+/// Evaluates the polynomial through the shares at any x, not just at 0.
+///
+/// # Arguments
+///
+/// * `shares` - A slice of tuples containing x and y coordinates.
+/// * `x` - The point to evaluate at.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The value at x of the unique polynomial of degree < `shares.len()` through the shares, or an
+/// error if the shares fail `check_shares`.
+pub fn interpolate_at(
+    shares: &[(BigInt, BigInt)],
+    x: &BigInt,
+    prime: &BigInt,
+) -> Result<BigInt, ShamirError> {
+    check_shares(shares, prime)?;
+    let xs: Vec<BigInt> = shares.iter().map(|(xj, _)| xj.clone()).collect();
+    let coefficients = lagrange_coefficients_at(&xs, x, prime)?;
+    let value = shares
+        .iter()
+        .zip(&coefficients)
+        .fold(BigInt::zero(), |acc, ((_, y), l)| (acc + y * l) % prime);
+    Ok(value)
+}
@@ -0,0 +1,536 @@
+//! Self-describing share encoding.
+//!
+//! A share is serialised as
+//!
+//! ```text
+//! version (1) | scheme (1) | threshold (2) | index length (2) | index | payload length (4) | payload | checksum (4)
+//! ```
+//!
+//! with all integers big-endian and the checksum being the first four bytes of SHA-256 over
+//! everything before it. The bytes can be written as hex, base64 or a list of BIP39 English words
+//! (11 bits per word), and `decode` recognises all three.
+
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bip39::Language;
+use num_bigint::{BigInt, Sign};
+use sha2::{Digest, Sha256};
+
+use crate::gf256::{self, ByteShare};
+use crate::shamir::{create_shares, modp_2048_prime, reconstruct_secret, ShamirError};
+
+/// Current version of the binary layout.
+pub const VERSION: u8 = 1;
+
+const CHECKSUM_LEN: usize = 4;
+
+/// The field a share lives in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    /// Byte-wise sharing over GF(2^8), see `gf256`.
+    Gf256,
+    /// Integer sharing modulo the 2048-bit MODP prime, see `shamir`.
+    Modp2048,
+}
+
+impl Scheme {
+    fn id(self) -> u8 {
+        match self {
+            Scheme::Gf256 => 1,
+            Scheme::Modp2048 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Scheme> {
+        match id {
+            1 => Some(Scheme::Gf256),
+            2 => Some(Scheme::Modp2048),
+            _ => None,
+        }
+    }
+
+    /// Parses the scheme name used on the command line.
+    pub fn from_name(name: &str) -> Option<Scheme> {
+        match name {
+            "gf256" => Some(Scheme::Gf256),
+            "modp2048" => Some(Scheme::Modp2048),
+            _ => None,
+        }
+    }
+
+    /// Longest secret in bytes the scheme can share.
+    pub fn max_secret_len(self) -> usize {
+        match self {
+            Scheme::Gf256 => usize::MAX,
+            // The secret is stored as 0x01 || bytes, which must stay below the 256-byte prime
+            Scheme::Modp2048 => 255,
+        }
+    }
+
+    /// Largest number of shares the scheme can hand out.
+    pub fn max_shares(self) -> usize {
+        match self {
+            Scheme::Gf256 => 255,
+            Scheme::Modp2048 => u16::MAX as usize,
+        }
+    }
+}
+
+/// Text encoding of a share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Hex,
+    Base64,
+    Words,
+}
+
+impl Format {
+    /// Parses the format name used on the command line.
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "hex" => Some(Format::Hex),
+            "base64" => Some(Format::Base64),
+            "words" => Some(Format::Words),
+            _ => None,
+        }
+    }
+}
+
+/// A single share together with everything needed to combine it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Share {
+    pub scheme: Scheme,
+    pub threshold: u16,
+    /// Big-endian x-coordinate.
+    pub index: Vec<u8>,
+    /// Big-endian y-value (Modp2048) or one y-byte per secret byte (Gf256).
+    pub payload: Vec<u8>,
+}
+
+/// Why a single share could not be read.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShareFormatError {
+    /// The text is neither hex, base64 nor a word list.
+    UnknownEncoding,
+    /// A word that is not in the BIP39 English list.
+    UnknownWord(String),
+    /// The text decoded, but the checksum does not match.
+    BadChecksum,
+    Truncated,
+    TrailingBytes,
+    UnsupportedVersion(u8),
+    UnknownScheme(u8),
+    /// The x-coordinate is zero or does not fit the scheme.
+    InvalidIndex,
+}
+
+impl fmt::Display for ShareFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareFormatError::UnknownEncoding => write!(f, "not a hex, base64 or word-list share"),
+            ShareFormatError::UnknownWord(word) => write!(f, "unknown word '{}'", word),
+            ShareFormatError::BadChecksum => write!(f, "checksum mismatch"),
+            ShareFormatError::Truncated => write!(f, "share is truncated"),
+            ShareFormatError::TrailingBytes => write!(f, "unexpected bytes after the share"),
+            ShareFormatError::UnsupportedVersion(version) => {
+                write!(f, "unsupported share version {}", version)
+            }
+            ShareFormatError::UnknownScheme(id) => write!(f, "unknown scheme id {}", id),
+            ShareFormatError::InvalidIndex => write!(f, "invalid share index"),
+        }
+    }
+}
+
+impl std::error::Error for ShareFormatError {}
+
+/// Why a set of shares could not be combined. `share` is the position in the input.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CombineError {
+    NoShares,
+    Malformed { share: usize, error: ShareFormatError },
+    SchemeMismatch { share: usize },
+    ThresholdMismatch { share: usize },
+    LengthMismatch { share: usize },
+    DuplicateIndex { share: usize },
+    NotEnoughShares { have: usize, need: usize },
+    /// The shares are well-formed but do not belong to the same secret.
+    Inconsistent,
+    /// The shares are well-formed but can't be interpolated, e.g. an index is zero.
+    Reconstruction(ShamirError),
+}
+
+impl fmt::Display for CombineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CombineError::NoShares => write!(f, "no shares given"),
+            CombineError::Malformed { share, error } => {
+                write!(f, "share {} is malformed: {}", share + 1, error)
+            }
+            CombineError::SchemeMismatch { share } => {
+                write!(f, "share {} uses a different scheme", share + 1)
+            }
+            CombineError::ThresholdMismatch { share } => {
+                write!(f, "share {} has a different threshold", share + 1)
+            }
+            CombineError::LengthMismatch { share } => {
+                write!(f, "share {} has a different length", share + 1)
+            }
+            CombineError::DuplicateIndex { share } => {
+                write!(f, "share {} repeats an earlier index", share + 1)
+            }
+            CombineError::NotEnoughShares { have, need } => {
+                write!(f, "{} shares given but {} are needed", have, need)
+            }
+            CombineError::Inconsistent => write!(f, "shares do not belong to the same secret"),
+            CombineError::Reconstruction(error) => write!(f, "reconstruction failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CombineError {}
+
+impl Share {
+    /// Serialises the share, including the trailing checksum.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![VERSION, self.scheme.id()];
+        bytes.extend_from_slice(&self.threshold.to_be_bytes());
+        bytes.extend_from_slice(&(self.index.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.index);
+        bytes.extend_from_slice(&(self.payload.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.payload);
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bytes
+    }
+
+    /// Parses serialised bytes. A word list can end in up to 10 zero padding bits, which show up
+    /// as at most one zero byte past the checksum and are accepted when `allow_padding` is set.
+    fn from_bytes(bytes: &[u8], allow_padding: bool) -> Result<Share, ShareFormatError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let version = reader.take(1)?[0];
+        let scheme_id = reader.take(1)?[0];
+        let threshold = u16::from_be_bytes(reader.take(2)?.try_into().unwrap());
+        let index_len = u16::from_be_bytes(reader.take(2)?.try_into().unwrap()) as usize;
+        let index = reader.take(index_len)?.to_vec();
+        let payload_len = u32::from_be_bytes(reader.take(4)?.try_into().unwrap()) as usize;
+        let payload = reader.take(payload_len)?.to_vec();
+        let body_len = reader.position;
+        let expected = reader.take(CHECKSUM_LEN)?;
+
+        let rest = &bytes[reader.position..];
+        if !(rest.is_empty() || allow_padding && rest == [0]) {
+            return Err(ShareFormatError::TrailingBytes);
+        }
+        if expected != checksum(&bytes[..body_len]) {
+            return Err(ShareFormatError::BadChecksum);
+        }
+        if version != VERSION {
+            return Err(ShareFormatError::UnsupportedVersion(version));
+        }
+        let scheme = Scheme::from_id(scheme_id).ok_or(ShareFormatError::UnknownScheme(scheme_id))?;
+        let index_fits = match scheme {
+            Scheme::Gf256 => index.len() == 1,
+            Scheme::Modp2048 => index.len() <= 2,
+        };
+        if !index_fits || index.iter().all(|&b| b == 0) {
+            return Err(ShareFormatError::InvalidIndex);
+        }
+
+        Ok(Share {
+            scheme,
+            threshold,
+            index,
+            payload,
+        })
+    }
+
+    /// The share index as an integer, so `01` and `0001` compare equal.
+    fn index_value(&self) -> u16 {
+        self.index.iter().fold(0, |value, &byte| (value << 8) | u16::from(byte))
+    }
+
+    /// Encodes the share as text.
+    pub fn encode(&self, format: Format) -> String {
+        let bytes = self.to_bytes();
+        match format {
+            Format::Hex => hex::encode(bytes),
+            Format::Base64 => BASE64.encode(bytes),
+            Format::Words => to_words(&bytes),
+        }
+    }
+
+    /// Decodes a share written in any of the supported formats.
+    pub fn decode(text: &str) -> Result<Share, ShareFormatError> {
+        let text = text.trim();
+        if text.contains(char::is_whitespace) {
+            return Share::from_bytes(&from_words(text)?, true);
+        }
+        // A hex string is also valid base64, so fall back to base64 when it doesn't parse as hex
+        let mut last_error = ShareFormatError::UnknownEncoding;
+        if let Ok(bytes) = hex::decode(text) {
+            match Share::from_bytes(&bytes, false) {
+                Ok(share) => return Ok(share),
+                Err(error) => last_error = error,
+            }
+        }
+        if let Ok(bytes) = BASE64.decode(text) {
+            match Share::from_bytes(&bytes, false) {
+                Ok(share) => return Ok(share),
+                Err(error) if last_error == ShareFormatError::UnknownEncoding => last_error = error,
+                Err(_) => {}
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// First bytes of SHA-256 over the serialised share.
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    Sha256::digest(bytes)[..CHECKSUM_LEN].try_into().unwrap()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ShareFormatError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(ShareFormatError::Truncated)?;
+        let slice = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(slice)
+    }
+}
+
+/// Writes the bytes as 11-bit groups, zero-padding the last group.
+fn to_words(bytes: &[u8]) -> String {
+    let words = Language::English.word_list();
+    let mut output = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 11 {
+            bits -= 11;
+            output.push(words[((buffer >> bits) & 0x7ff) as usize]);
+        }
+    }
+    if bits > 0 {
+        output.push(words[((buffer << (11 - bits)) & 0x7ff) as usize]);
+    }
+    output.join(" ")
+}
+
+/// Reverses `to_words`, dropping the padding bits that don't make up a whole byte.
+fn from_words(text: &str) -> Result<Vec<u8>, ShareFormatError> {
+    let mut output = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for word in text.split_whitespace() {
+        let value = Language::English
+            .find_word(&word.to_lowercase())
+            .ok_or_else(|| ShareFormatError::UnknownWord(word.to_string()))?;
+        buffer = (buffer << 11) | value as u32;
+        bits += 11;
+        while bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    Ok(output)
+}
+
+/// Splits a secret into encoded-ready shares under the given scheme.
+///
+/// # Arguments
+///
+/// * `secret` - The bytes to be shared, at most `scheme.max_secret_len()` long.
+/// * `scheme` - The field to share in.
+/// * `threshold` - The minimum number of shares required to reconstruct the secret.
+/// * `num_shares` - The total number of shares to create, at most `scheme.max_shares()`.
+///
+/// # Returns
+///
+/// A vector of `Share`s with indices `1..=num_shares`.
+pub fn split(secret: &[u8], scheme: Scheme, threshold: usize, num_shares: usize) -> Vec<Share> {
+    assert!(secret.len() <= scheme.max_secret_len(), "secret is too long for the scheme");
+    assert!(num_shares <= scheme.max_shares(), "too many shares for the scheme");
+    let threshold_u16 = threshold as u16;
+
+    match scheme {
+        Scheme::Gf256 => gf256::split(secret, threshold, num_shares)
+            .into_iter()
+            .map(|share| Share {
+                scheme,
+                threshold: threshold_u16,
+                index: vec![share.x],
+                payload: share.y,
+            })
+            .collect(),
+        Scheme::Modp2048 => {
+            // The 0x01 prefix keeps leading zero bytes of the secret
+            let mut prefixed = vec![1u8];
+            prefixed.extend_from_slice(secret);
+            let value = BigInt::from_bytes_be(Sign::Plus, &prefixed);
+            let prime = modp_2048_prime();
+            let width = prime.to_bytes_be().1.len();
+            create_shares(num_shares, threshold, &value, &prime)
+                .into_iter()
+                .map(|(x, y)| {
+                    // Pad y to the prime's width so every share has the same length
+                    let y = y.to_bytes_be().1;
+                    let mut payload = vec![0u8; width - y.len()];
+                    payload.extend_from_slice(&y);
+                    Share {
+                        scheme,
+                        threshold: threshold_u16,
+                        index: x.to_bytes_be().1,
+                        payload,
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Decodes and combines text shares, reporting the position of the first bad share.
+///
+/// # Arguments
+///
+/// * `encoded` - Shares in any supported text format, at least `threshold` of them.
+///
+/// # Returns
+///
+/// The reconstructed secret bytes.
+pub fn combine<S: AsRef<str>>(encoded: &[S]) -> Result<Vec<u8>, CombineError> {
+    let shares = encoded
+        .iter()
+        .enumerate()
+        .map(|(share, text)| {
+            Share::decode(text.as_ref()).map_err(|error| CombineError::Malformed { share, error })
+        })
+        .collect::<Result<Vec<Share>, CombineError>>()?;
+
+    let first = shares.first().ok_or(CombineError::NoShares)?;
+    for (i, share) in shares.iter().enumerate().skip(1) {
+        if share.scheme != first.scheme {
+            return Err(CombineError::SchemeMismatch { share: i });
+        }
+        if share.threshold != first.threshold {
+            return Err(CombineError::ThresholdMismatch { share: i });
+        }
+        if share.payload.len() != first.payload.len() {
+            return Err(CombineError::LengthMismatch { share: i });
+        }
+        if shares[..i].iter().any(|other| other.index_value() == share.index_value()) {
+            return Err(CombineError::DuplicateIndex { share: i });
+        }
+    }
+    if shares.len() < first.threshold as usize {
+        return Err(CombineError::NotEnoughShares {
+            have: shares.len(),
+            need: first.threshold as usize,
+        });
+    }
+
+    match first.scheme {
+        Scheme::Gf256 => {
+            let byte_shares: Vec<ByteShare> = shares
+                .iter()
+                .map(|share| ByteShare {
+                    x: share.index[0],
+                    y: share.payload.clone(),
+                })
+                .collect();
+            gf256::combine(&byte_shares).map_err(CombineError::Reconstruction)
+        }
+        Scheme::Modp2048 => {
+            let points: Vec<(BigInt, BigInt)> = shares
+                .iter()
+                .map(|share| {
+                    (
+                        BigInt::from_bytes_be(Sign::Plus, &share.index),
+                        BigInt::from_bytes_be(Sign::Plus, &share.payload),
+                    )
+                })
+                .collect();
+            let value = reconstruct_secret(&points, &modp_2048_prime())
+                .map_err(CombineError::Reconstruction)?
+                .to_bytes_be()
+                .1;
+            match value.split_first() {
+                Some((1, secret)) => Ok(secret.to_vec()),
+                _ => Err(CombineError::Inconsistent),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_in_every_format() {
+        let secret = b"\x00\x00leading zeros survive";
+        for scheme in [Scheme::Gf256, Scheme::Modp2048] {
+            for format in [Format::Hex, Format::Base64, Format::Words] {
+                let encoded: Vec<String> = split(secret, scheme, 3, 5)
+                    .iter()
+                    .map(|share| share.encode(format))
+                    .collect();
+                assert_eq!(combine(&encoded[1..4]).unwrap(), secret);
+                assert_eq!(
+                    combine(&encoded[..2]),
+                    Err(CombineError::NotEnoughShares { have: 2, need: 3 })
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reports_the_bad_share() {
+        let mut encoded: Vec<String> = split(b"secret", Scheme::Gf256, 2, 3)
+            .iter()
+            .map(|share| share.encode(Format::Hex))
+            .collect();
+
+        // Flip one hex digit of the payload in the second share
+        let mut corrupted = encoded[1].clone().into_bytes();
+        let position = corrupted.len() - 12;
+        corrupted[position] = if corrupted[position] == b'0' { b'1' } else { b'0' };
+        encoded[1] = String::from_utf8(corrupted).unwrap();
+        assert_eq!(
+            combine(&encoded),
+            Err(CombineError::Malformed {
+                share: 1,
+                error: ShareFormatError::BadChecksum
+            })
+        );
+
+        encoded[1] = encoded[0].clone();
+        assert_eq!(combine(&encoded), Err(CombineError::DuplicateIndex { share: 1 }));
+
+        let other = split(b"secret", Scheme::Modp2048, 2, 3)[2].encode(Format::Base64);
+        encoded[1] = other;
+        assert_eq!(combine(&encoded), Err(CombineError::SchemeMismatch { share: 1 }));
+    }
+
+    #[test]
+    fn padded_index_is_a_duplicate() {
+        let shares = split(b"secret", Scheme::Modp2048, 2, 3);
+        let mut padded = shares[0].clone();
+        padded.index.insert(0, 0);
+        let encoded = [shares[0].encode(Format::Hex), padded.encode(Format::Hex)];
+        assert_eq!(combine(&encoded), Err(CombineError::DuplicateIndex { share: 1 }));
+    }
+}
@@ -0,0 +1,188 @@
+//! Weighted threshold sharing: a participant with weight w holds w points of the polynomial.
+//!
+//! With a degree T - 1 polynomial, any set of participants whose weights add up to T can
+//! reconstruct. Weights 3 for two officers and 1 for four staff with T = 6 give "2 officers, or
+//! 1 officer plus 3 staff": the staff alone only reach 4.
+
+use num_bigint::BigInt;
+
+use crate::shamir::{
+    evaluate_polynomial, generate_coefficients, modp_2048_prime, reconstruct_secret, ShamirError,
+};
+
+/// All points held by one participant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedShare {
+    pub participant: usize,
+    pub points: Vec<(BigInt, BigInt)>,
+}
+
+impl WeightedShare {
+    /// The participant's weight, i.e. the number of points it holds.
+    pub fn weight(&self) -> usize {
+        self.points.len()
+    }
+}
+
+/// Creates one weighted share per participant.
+///
+/// # Arguments
+///
+/// * `secret` - The secret to be shared.
+/// * `weights` - The weight of every participant; participant i gets `weights[i]` points.
+/// * `threshold` - The total weight T needed to reconstruct the secret.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The shares in participant order, with consecutive x-coordinates starting at 1, or an error if
+/// the threshold is zero or above the total weight.
+pub fn create_weighted_shares(
+    secret: &BigInt,
+    weights: &[usize],
+    threshold: usize,
+    prime: &BigInt,
+) -> Result<Vec<WeightedShare>, ShamirError> {
+    let total: usize = weights.iter().sum();
+    if threshold == 0 || threshold > total {
+        return Err(ShamirError::InvalidThreshold {
+            threshold,
+            num_shares: total,
+        });
+    }
+    let coefficients = generate_coefficients(threshold, secret, prime);
+    let mut next_x = 1usize;
+    Ok(weights
+        .iter()
+        .enumerate()
+        .map(|(participant, &weight)| {
+            let points = (next_x..next_x + weight)
+                .map(|x| {
+                    let x = BigInt::from(x);
+                    let y = evaluate_polynomial(&x, &coefficients, prime);
+                    (x, y)
+                })
+                .collect();
+            next_x += weight;
+            WeightedShare {
+                participant,
+                points,
+            }
+        })
+        .collect())
+}
+
+/// The shares with every participant kept only once, in order of first appearance.
+fn distinct_participants(shares: &[WeightedShare]) -> Vec<&WeightedShare> {
+    let mut distinct: Vec<&WeightedShare> = Vec::new();
+    for share in shares {
+        if !distinct
+            .iter()
+            .any(|other| other.participant == share.participant)
+        {
+            distinct.push(share);
+        }
+    }
+    distinct
+}
+
+/// Decides whether the participants' combined weight reaches the threshold.
+pub fn is_authorized(shares: &[WeightedShare], threshold: usize) -> bool {
+    // A participant listed twice only counts once
+    let weight: usize = distinct_participants(shares)
+        .iter()
+        .map(|share| share.weight())
+        .sum();
+    weight >= threshold
+}
+
+/// Reconstructs the secret from the participants' weighted shares.
+///
+/// # Arguments
+///
+/// * `shares` - The shares of the participating holders.
+/// * `threshold` - The total weight T needed to reconstruct the secret.
+/// * `prime` - A large prime number for modulo operations.
+///
+/// # Returns
+///
+/// The secret, or `NotAuthorized` before any interpolation if the weight is too low.
+pub fn reconstruct_weighted(
+    shares: &[WeightedShare],
+    threshold: usize,
+    prime: &BigInt,
+) -> Result<BigInt, ShamirError> {
+    if !is_authorized(shares, threshold) {
+        return Err(ShamirError::NotAuthorized);
+    }
+    // Points come from the same participants that `is_authorized` counted
+    let points: Vec<(BigInt, BigInt)> = distinct_participants(shares)
+        .into_iter()
+        .flat_map(|share| share.points.iter().cloned())
+        .take(threshold)
+        .collect();
+    reconstruct_secret(&points, prime)
+}
+
+/// "2 officers, or 1 officer plus 3 staff" with weights 3, 3, 1, 1, 1, 1 and threshold 6.
+pub fn run_demo() {
+    let prime = modp_2048_prime();
+    let secret = BigInt::from(123456789);
+    let shares = create_weighted_shares(&secret, &[3, 3, 1, 1, 1, 1], 6, &prime).unwrap();
+    let names = [
+        "officer A",
+        "officer B",
+        "staff 1",
+        "staff 2",
+        "staff 3",
+        "staff 4",
+    ];
+
+    for group in [vec![0, 1], vec![0, 2, 3, 4], vec![1, 5], vec![2, 3, 4, 5]] {
+        let selected: Vec<WeightedShare> = group.iter().map(|&i| shares[i].clone()).collect();
+        let members: Vec<&str> = group.iter().map(|&i| names[i]).collect();
+        match reconstruct_weighted(&selected, 6, &prime) {
+            Ok(value) => println!("{}: recovered {}", members.join(", "), value),
+            Err(error) => println!("{}: {}", members.join(", "), error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_decides_authorization() {
+        let prime = BigInt::from(2305843009213693951u64); // 2^61 - 1
+        let secret = BigInt::from(777);
+        let shares = create_weighted_shares(&secret, &[3, 3, 1, 1, 1, 1], 6, &prime).unwrap();
+        assert_eq!(shares[1].points[0].0, BigInt::from(4));
+
+        let officers = [shares[0].clone(), shares[1].clone()];
+        assert_eq!(
+            reconstruct_weighted(&officers, 6, &prime),
+            Ok(secret.clone())
+        );
+        let mixed = [
+            shares[1].clone(),
+            shares[2].clone(),
+            shares[4].clone(),
+            shares[5].clone(),
+        ];
+        assert_eq!(reconstruct_weighted(&mixed, 6, &prime), Ok(secret.clone()));
+
+        let staff = shares[2..].to_vec();
+        assert!(!is_authorized(&staff, 6));
+        let repeated = [shares[0].clone(), shares[0].clone()];
+        assert_eq!(
+            reconstruct_weighted(&repeated, 6, &prime),
+            Err(ShamirError::NotAuthorized)
+        );
+        // A repeated participant next to an authorized set does not break reconstruction
+        let repeated = [shares[0].clone(), shares[0].clone(), shares[1].clone()];
+        assert!(is_authorized(&repeated, 6));
+        assert_eq!(reconstruct_weighted(&repeated, 6, &prime), Ok(secret));
+        assert!(create_weighted_shares(&BigInt::from(1), &[1, 1], 3, &prime).is_err());
+    }
+}
@@ -1,32 +1,21 @@
+mod p521;
+mod robust;
+
 use num_bigint::{BigInt, BigUint, RandBigInt, ToBigInt};
 use num_traits::{One, Zero};
+use p521::Fe;
 use rand::thread_rng;
+use std::fmt;
+use std::sync::LazyLock;
+use std::time::Instant;
 
-// a large prime number (2^521 - 1)
-const PRIME: &str = "6864797660130609714981900799081393217269435300143305409394463459185543183397656052122559640661454554977296311391480858037121987999716643812574028291115057151";
+// a large prime number (2^521 - 1), parsed once on first use
+static PRIME: LazyLock<BigUint> = LazyLock::new(|| {
+    BigUint::parse_bytes(b"6864797660130609714981900799081393217269435300143305409394463459185543183397656052122559640661454554977296311391480858037121987999716643812574028291115057151", 10).unwrap()
+});
 
-/// Computes (base^exp) % modulus using the modular exponentiation algorithm.
-///
-/// # Arguments
-/// * `base` - The base of the exponentiation
-/// * `exp` - The exponent
-/// * `modulus` - The modulus for the operation
-///
-/// # Returns
-/// The result of (base^exp) % modulus as a BigUint
-fn mod_pow(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
-    let mut result = BigUint::one();
-    let mut base = base.clone();
-    let mut exp = exp.clone();
-    while exp > BigUint::zero() {
-        if exp.bit(0) {
-            result = (result * &base) % modulus;
-        }
-        base = (&base * &base) % modulus;
-        exp >>= 1;
-    }
-    result
-}
+// The same prime for the signed arithmetic of the BigInt reference code
+static PRIME_INT: LazyLock<BigInt> = LazyLock::new(|| PRIME.to_bigint().unwrap());
 
 /// Computes the modular multiplicative inverse of 'a' modulo 'm' using the extended Euclidean algorithm.
 ///
@@ -35,11 +24,9 @@ fn mod_pow(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
 /// * `m` - The modulus
 ///
 /// # Returns
-/// The modular multiplicative inverse of 'a' modulo 'm' as a BigInt
-///
-/// # Panics
-/// Panics if 'a' is not invertible modulo 'm'
-fn mod_inverse(a: &BigInt, m: &BigInt) -> BigInt {
+/// The modular multiplicative inverse of 'a' modulo 'm' as a BigInt, or None if 'a' is not
+/// invertible modulo 'm'
+fn mod_inverse(a: &BigInt, m: &BigInt) -> Option<BigInt> {
     let mut t = BigInt::zero();
     let mut new_t = BigInt::one();
     let mut r = m.clone();
@@ -54,43 +41,205 @@ fn mod_inverse(a: &BigInt, m: &BigInt) -> BigInt {
     }
 
     if r > BigInt::one() {
-        panic!("a is not invertible");
+        return None;
     }
     if t < BigInt::zero() {
         t += m;
     }
-    t
+    Some(t)
+}
+
+/// Error returned when shares can't be used for reconstruction.
+#[derive(Debug, PartialEq)]
+enum ShamirError {
+    NotEnoughShares {
+        have: usize,
+        need: usize,
+    },
+    /// The threshold is zero, or larger than the number of shares
+    InvalidThreshold(usize),
+    /// The secret is not below the prime, so it would be shared reduced
+    SecretOutOfRange,
+    /// A share ID is zero, that share would be the secret itself
+    ZeroIndex,
+    DuplicateIndex(BigUint),
+    /// A share ID or value is not below the prime, so it is from a different field
+    ValueOutOfRange(BigUint),
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShamirError::NotEnoughShares { have, need } => {
+                write!(f, "{} shares given but {} are needed", have, need)
+            }
+            ShamirError::InvalidThreshold(threshold) => {
+                write!(f, "invalid threshold {}", threshold)
+            }
+            ShamirError::SecretOutOfRange => write!(f, "the secret is not below the prime"),
+            ShamirError::ZeroIndex => write!(f, "a share has ID 0"),
+            ShamirError::DuplicateIndex(x) => write!(f, "more than one share has ID {}", x),
+            ShamirError::ValueOutOfRange(value) => {
+                write!(f, "share value {} is not below the prime", value)
+            }
+        }
+    }
+}
+
+/// Checks the shares a reconstruction with the given threshold will use.
+///
+/// # Arguments
+/// * `shares` - A slice of tuples, where each tuple contains a share ID and its corresponding value
+/// * `threshold` - The number of shares used for reconstruction
+///
+/// # Returns
+/// The first `threshold` shares, or an error if there are too few of them or an ID or value is
+/// invalid
+fn check_shares(
+    shares: &[(BigUint, BigUint)],
+    threshold: usize,
+) -> Result<&[(BigUint, BigUint)], ShamirError> {
+    if threshold == 0 {
+        return Err(ShamirError::InvalidThreshold(threshold));
+    }
+    if shares.len() < threshold {
+        return Err(ShamirError::NotEnoughShares {
+            have: shares.len(),
+            need: threshold,
+        });
+    }
+    let shares = &shares[..threshold];
+    for (i, (x, y)) in shares.iter().enumerate() {
+        if let Some(value) = [x, y].into_iter().find(|value| **value >= *PRIME) {
+            return Err(ShamirError::ValueOutOfRange(value.clone()));
+        }
+        if x.is_zero() {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if shares[..i].iter().any(|(other, _)| other == x) {
+            return Err(ShamirError::DuplicateIndex(x.clone()));
+        }
+    }
+    Ok(shares)
 }
 
 /// Generates Shamir's Secret Sharing scheme shares for a given secret.
 ///
+/// The shares get the IDs 1 to `num_shares`, see `generate_shares_at`.
+///
 /// # Arguments
 /// * `secret` - The secret to be shared
 /// * `num_shares` - The total number of shares to generate
 /// * `threshold` - The minimum number of shares required to reconstruct the secret
 ///
 /// # Returns
-/// A vector of tuples, where each tuple contains a share ID and its corresponding value
+/// A vector of tuples, where each tuple contains a share ID and its corresponding value, or an
+/// error if the threshold or the secret is invalid
 fn generate_shares(
     secret: &BigUint,
     num_shares: usize,
     threshold: usize,
+) -> Result<Vec<(BigUint, BigUint)>, ShamirError> {
+    let xs: Vec<BigUint> = (1..=num_shares).map(BigUint::from).collect();
+    generate_shares_at(secret, &xs, threshold)
+}
+
+/// Generates shares at caller-chosen x-coordinates, such as participant IDs.
+///
+/// The polynomial is evaluated with Horner's rule on P-521 field elements, so every step is one
+/// fixed-width multiplication with shift-and-add reduction.
+///
+/// # Arguments
+/// * `secret` - The secret to be shared, below the prime
+/// * `xs` - The share IDs, non-zero, distinct and below the prime
+/// * `threshold` - The minimum number of shares required to reconstruct the secret, between 1 and
+///   the number of IDs
+///
+/// # Returns
+/// A vector of tuples, where each tuple contains a share ID and its corresponding value, or an
+/// error if the threshold or the secret is invalid, or a share ID is zero, repeated or not below
+/// the prime, since a share at zero is the secret itself and repeated IDs cannot be interpolated
+fn generate_shares_at(
+    secret: &BigUint,
+    xs: &[BigUint],
+    threshold: usize,
+) -> Result<Vec<(BigUint, BigUint)>, ShamirError> {
+    if threshold == 0 || threshold > xs.len() {
+        return Err(ShamirError::InvalidThreshold(threshold));
+    }
+    if *secret >= *PRIME {
+        return Err(ShamirError::SecretOutOfRange);
+    }
+    for (i, x) in xs.iter().enumerate() {
+        if *x >= *PRIME {
+            return Err(ShamirError::ValueOutOfRange(x.clone()));
+        }
+        if x.is_zero() {
+            return Err(ShamirError::ZeroIndex);
+        }
+        if xs[..i].contains(x) {
+            return Err(ShamirError::DuplicateIndex(x.clone()));
+        }
+    }
+    let xs: Vec<Fe> = xs.iter().map(Fe::from_biguint).collect();
+
+    let mut rng = thread_rng();
+    let mut coefficients = vec![Fe::from_biguint(secret)];
+    for _ in 1..threshold {
+        coefficients.push(Fe::from_biguint(&rng.gen_biguint_below(&PRIME)));
+    }
+
+    Ok(xs
+        .into_iter()
+        .map(|x| (x.to_biguint(), evaluate(&coefficients, x).to_biguint()))
+        .collect())
+}
+
+/// Evaluates a polynomial on P-521 field elements with Horner's rule.
+///
+/// # Arguments
+/// * `coefficients` - The coefficients, lowest degree first
+/// * `x` - The point to evaluate at
+///
+/// # Returns
+/// The value of the polynomial at x
+fn evaluate(coefficients: &[Fe], x: Fe) -> Fe {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Fe::ZERO, |y, coeff| y * x + *coeff)
+}
+
+/// Generates shares like `generate_shares`, evaluating the polynomial with Horner's rule.
+///
+/// This is the generic `BigUint` version, kept to compare against the P-521 field arithmetic.
+///
+/// # Arguments
+/// * `secret` - The secret to be shared
+/// * `num_shares` - The total number of shares to generate
+/// * `threshold` - The minimum number of shares required to reconstruct the secret
+///
+/// # Returns
+/// A vector of tuples, where each tuple contains a share ID and its corresponding value
+fn generate_shares_horner(
+    secret: &BigUint,
+    num_shares: usize,
+    threshold: usize,
 ) -> Vec<(BigUint, BigUint)> {
-    let prime = BigUint::parse_bytes(PRIME.as_bytes(), 10).unwrap();
+    let prime = &*PRIME;
     let mut rng = thread_rng();
     let mut coefficients = vec![secret.clone()];
     for _ in 1..threshold {
-        coefficients.push(rng.gen_biguint_below(&prime));
+        coefficients.push(rng.gen_biguint_below(prime));
     }
 
     (1..=num_shares)
         .map(|x| {
             let x_biguint = BigUint::from(x);
-            let mut y = BigUint::zero();
-            for (i, coeff) in coefficients.iter().enumerate() {
-                y += coeff * mod_pow(&x_biguint, &BigUint::from(i), &prime);
-                y %= &prime;
-            }
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(BigUint::zero(), |y, coeff| (y * &x_biguint + coeff) % prime);
             (x_biguint, y)
         })
         .collect()
@@ -98,15 +247,75 @@ fn generate_shares(
 
 /// Reconstructs the secret from a set of Shamir's Secret Sharing scheme shares.
 ///
+/// The Lagrange weights are computed on P-521 field elements, and all denominators share a single
+/// inversion by Fermat's little theorem, so no step depends on the share values through its
+/// running time.
+///
 /// # Arguments
 /// * `shares` - A slice of tuples, where each tuple contains a share ID and its corresponding value
 /// * `threshold` - The number of shares used for reconstruction (must match the threshold used in generation)
 ///
 /// # Returns
-/// The reconstructed secret as a BigUint
-fn reconstruct_secret(shares: &[(BigUint, BigUint)], threshold: usize) -> BigUint {
-    let prime = BigUint::parse_bytes(PRIME.as_bytes(), 10).unwrap();
-    let prime_int = prime.to_bigint().unwrap();
+/// The reconstructed secret as a BigUint, or an error if there are fewer than `threshold` shares
+/// or an ID or value is invalid
+fn reconstruct_secret(
+    shares: &[(BigUint, BigUint)],
+    threshold: usize,
+) -> Result<BigUint, ShamirError> {
+    let points: Vec<(Fe, Fe)> = check_shares(shares, threshold)?
+        .iter()
+        .map(|(x, y)| (Fe::from_biguint(x), Fe::from_biguint(y)))
+        .collect();
+
+    // The numerator of term i is the product of all x_j divided by x_i, so every term is
+    // y_i * x_product / (x_i * denominator_i)
+    let x_product = points.iter().fold(Fe::ONE, |acc, (x, _)| acc * *x);
+    let denominators: Vec<Fe> = points
+        .iter()
+        .enumerate()
+        .map(|(i, (xi, _))| {
+            points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(*xi, |acc, (_, (xj, _))| acc * (*xj - *xi))
+        })
+        .collect();
+
+    // Invert all denominators with one inversion: invert the product of all of them, then peel
+    // them off one at a time from the back
+    let mut prefix = Vec::with_capacity(threshold);
+    let mut running = Fe::ONE;
+    for denominator in &denominators {
+        prefix.push(running);
+        running = running * *denominator;
+    }
+    let mut inverse = running.invert();
+    let mut secret = Fe::ZERO;
+    for i in (0..threshold).rev() {
+        secret = secret + points[i].1 * x_product * inverse * prefix[i];
+        inverse = inverse * denominators[i];
+    }
+
+    Ok(secret.to_biguint())
+}
+
+/// Reconstructs the secret like `reconstruct_secret` with generic `BigUint` arithmetic, kept to
+/// compare against the P-521 field arithmetic.
+///
+/// # Arguments
+/// * `shares` - A slice of tuples, where each tuple contains a share ID and its corresponding value
+/// * `threshold` - The number of shares used for reconstruction (must match the threshold used in generation)
+///
+/// # Returns
+/// The reconstructed secret as a BigUint, or an error if there are fewer than `threshold` shares
+/// or an ID or value is invalid
+fn reconstruct_secret_biguint(
+    shares: &[(BigUint, BigUint)],
+    threshold: usize,
+) -> Result<BigUint, ShamirError> {
+    let shares = check_shares(shares, threshold)?;
+    let prime_int = &*PRIME_INT;
     let mut secret = BigInt::zero();
 
     for i in 0..threshold {
@@ -114,51 +323,239 @@ fn reconstruct_secret(shares: &[(BigUint, BigUint)], threshold: usize) -> BigUin
         let mut numerator = BigInt::one();
         let mut denominator = BigInt::one();
 
-        for j in 0..threshold {
+        for (j, (xj, _)) in shares.iter().enumerate() {
             if i != j {
-                let (xj, _) = &shares[j];
                 numerator *= xj.to_bigint().unwrap();
-                numerator %= &prime_int;
+                numerator %= prime_int;
                 denominator *=
-                    (xj.to_bigint().unwrap() - xi.to_bigint().unwrap() + &prime_int) % &prime_int;
-                denominator %= &prime_int;
+                    (xj.to_bigint().unwrap() - xi.to_bigint().unwrap() + prime_int) % prime_int;
+                denominator %= prime_int;
             }
         }
 
-        let term = yi.to_bigint().unwrap() * numerator * mod_inverse(&denominator, &prime_int);
+        // The IDs are distinct and below the prime, so every denominator is invertible
+        let inverse = mod_inverse(&denominator, prime_int)
+            .ok_or_else(|| ShamirError::DuplicateIndex(xi.clone()))?;
+        let term = yi.to_bigint().unwrap() * numerator * inverse;
         secret += term;
-        secret %= &prime_int;
+        secret %= prime_int;
     }
 
     if secret < BigInt::zero() {
         secret += prime_int;
     }
-    secret.to_biguint().unwrap()
+    Ok(secret.to_biguint().unwrap())
 }
 
 /// The main function that demonstrates the usage of Shamir's Secret Sharing scheme.
 ///
 /// This function:
 /// 1. Creates a secret
-/// 2. Generates shares for the secret
+/// 2. Generates shares for the secret, on the P-521 field and with generic BigUint arithmetic
 /// 3. Reconstructs the secret from a subset of shares
 /// 4. Compares the reconstructed secret with the original
+/// 5. Recovers the secret from a smaller set of shares with some of them corrupted
+/// 6. Shares the secret at participant IDs
+/// 7. Shows the errors for too few shares, repeated shares and repeated share IDs
 fn main() {
     let secret = BigUint::parse_bytes(b"123456789012345678901234567890", 10).unwrap();
     let num_shares = 450;
     let threshold = 400;
 
-    let shares = generate_shares(&secret, num_shares, threshold);
+    let start = Instant::now();
+    let shares = generate_shares(&secret, num_shares, threshold).unwrap();
+    println!("Generated {} shares in {:?}", num_shares, start.elapsed());
+    let start = Instant::now();
+    let biguint_shares = generate_shares_horner(&secret, num_shares, threshold);
+    println!("Generated {} shares with BigUint arithmetic in {:?}", num_shares, start.elapsed());
+    let start = Instant::now();
+    let biguint_secret = reconstruct_secret_biguint(&biguint_shares[50..], threshold).unwrap();
+    println!("Reconstructed with BigUint arithmetic in {:?}", start.elapsed());
+    println!(
+        "BigUint shares reconstruct correctly: {}",
+        biguint_secret == secret
+            && reconstruct_secret(&biguint_shares[50..], threshold) == Ok(secret.clone())
+    );
     println!("Shares:");
     for (i, share) in shares.iter().enumerate() {
         println!("Share {}: ({}, {})", i + 1, share.0, share.1);
     }
 
-    let reconstructed_secret = reconstruct_secret(&shares[0..threshold], threshold);
+    let start = Instant::now();
+    let reconstructed_secret = reconstruct_secret(&shares[0..threshold], threshold).unwrap();
+    println!("Reconstructed in {:?}", start.elapsed());
     println!("Reconstructed secret:      {}", reconstructed_secret);
     println!("Original secret:           {}", secret);
     println!(
         "Reconstruction successful: {}",
         reconstructed_secret == secret
     );
+
+    // Two of ten shares are corrupted, with threshold 4 up to (10 - 4) / 2 = 3 can be corrected
+    let threshold = 4;
+    let mut shares = generate_shares(&secret, 10, threshold).unwrap();
+    shares[2].1 += 1u32;
+    shares[7].1 = BigUint::from(42u32);
+    println!(
+        "Plain reconstruction with corrupted shares correct: {}",
+        reconstruct_secret(&shares, threshold) == Ok(secret.clone())
+    );
+    match robust::robust_reconstruct(&shares, threshold) {
+        Ok((recovered, faulty)) => {
+            println!("Robust reconstruction correct: {}", recovered == secret);
+            println!("Faulty share IDs: {:?}", faulty);
+        }
+        Err(error) => println!("Robust reconstruction failed: {}", error),
+    }
+
+    // Shares at participant IDs instead of 1..=n
+    let participant_ids: Vec<BigUint> = [1001u32, 2002, 3003, 4004, 5005]
+        .into_iter()
+        .map(BigUint::from)
+        .collect();
+    let id_shares = generate_shares_at(&secret, &participant_ids, threshold).unwrap();
+    println!(
+        "Reconstruction from participant IDs successful: {}",
+        reconstruct_secret(&id_shares[1..], threshold) == Ok(secret.clone())
+    );
+
+    // Invalid share sets give an error instead of a panic
+    if let Err(error) = reconstruct_secret(&id_shares[..2], threshold) {
+        println!("Reconstruction from 2 shares failed: {}", error);
+    }
+    let repeated = vec![id_shares[0].clone(); threshold];
+    if let Err(error) = reconstruct_secret(&repeated, threshold) {
+        println!("Reconstruction from repeated shares failed: {}", error);
+    }
+    let repeated_ids = vec![participant_ids[0].clone(); threshold];
+    if let Err(error) = generate_shares_at(&secret, &repeated_ids, threshold) {
+        println!("Sharing at repeated IDs failed: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes (base^exp) % modulus using the modular exponentiation algorithm.
+    ///
+    /// # Arguments
+    /// * `base` - The base of the exponentiation
+    /// * `exp` - The exponent
+    /// * `modulus` - The modulus for the operation
+    ///
+    /// # Returns
+    /// The result of (base^exp) % modulus as a BigUint
+    fn mod_pow(base: &BigUint, exp: &BigUint, modulus: &BigUint) -> BigUint {
+        let mut result = BigUint::one();
+        let mut base = base.clone();
+        let mut exp = exp.clone();
+        while exp > BigUint::zero() {
+            if exp.bit(0) {
+                result = (result * &base) % modulus;
+            }
+            base = (&base * &base) % modulus;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// The original share generation, summing coeff * x^i with `mod_pow` for every term. It is
+    /// the reference the P-521 field arithmetic is checked against.
+    ///
+    /// # Arguments
+    /// * `coefficients` - The polynomial, secret first
+    /// * `num_shares` - The total number of shares to generate
+    ///
+    /// # Returns
+    /// A vector of tuples, where each tuple contains a share ID and its corresponding value
+    fn generate_shares_mod_pow(
+        coefficients: &[BigUint],
+        num_shares: usize,
+    ) -> Vec<(BigUint, BigUint)> {
+        (1..=num_shares)
+            .map(|x| {
+                let x_biguint = BigUint::from(x);
+                let mut y = BigUint::zero();
+                for (i, coeff) in coefficients.iter().enumerate() {
+                    y += coeff * mod_pow(&x_biguint, &BigUint::from(i), &PRIME);
+                    y %= &*PRIME;
+                }
+                (x_biguint, y)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn field_evaluation_matches_reference() {
+        let mut rng = thread_rng();
+        let mut coefficients: Vec<BigUint> =
+            (0..20).map(|_| rng.gen_biguint_below(&PRIME)).collect();
+        coefficients[1] = &*PRIME - 1u32;
+        let reference = generate_shares_mod_pow(&coefficients, 50);
+
+        let field_coefficients: Vec<Fe> = coefficients.iter().map(Fe::from_biguint).collect();
+        for (x, y) in &reference {
+            assert_eq!(evaluate(&field_coefficients, Fe::from_biguint(x)).to_biguint(), *y);
+        }
+
+        // Both reconstructions recover the constant term from any 20 reference shares
+        assert_eq!(reconstruct_secret(&reference[30..], 20), Ok(coefficients[0].clone()));
+        assert_eq!(
+            reconstruct_secret_biguint(&reference[..20], 20),
+            Ok(coefficients[0].clone())
+        );
+    }
+
+    #[test]
+    fn shares_round_trip() {
+        let secret = &*PRIME - 1u32;
+        let shares = generate_shares(&secret, 12, 7).unwrap();
+        assert_eq!(reconstruct_secret(&shares[5..], 7), Ok(secret.clone()));
+        assert_eq!(reconstruct_secret_biguint(&shares[..7], 7), Ok(secret.clone()));
+        assert_eq!(
+            reconstruct_secret(&shares[..6], 7),
+            Err(ShamirError::NotEnoughShares { have: 6, need: 7 })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_share_ids() {
+        let secret = BigUint::from(5u32);
+        let ids = |id: BigUint| [BigUint::from(3u32), id];
+        assert_eq!(
+            generate_shares_at(&secret, &ids(BigUint::zero()), 2),
+            Err(ShamirError::ZeroIndex)
+        );
+        assert_eq!(
+            generate_shares_at(&secret, &ids(BigUint::from(3u32)), 2),
+            Err(ShamirError::DuplicateIndex(BigUint::from(3u32)))
+        );
+        assert_eq!(
+            generate_shares_at(&secret, &ids(&*PRIME + 3u32), 2),
+            Err(ShamirError::ValueOutOfRange(&*PRIME + 3u32))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_threshold_and_secret() {
+        let ids = [BigUint::from(3u32), BigUint::from(8u32)];
+        let secret = BigUint::from(5u32);
+        assert_eq!(
+            generate_shares_at(&secret, &ids, 0),
+            Err(ShamirError::InvalidThreshold(0))
+        );
+        assert_eq!(
+            generate_shares_at(&secret, &ids, 3),
+            Err(ShamirError::InvalidThreshold(3))
+        );
+        assert_eq!(
+            generate_shares_at(&PRIME, &ids, 2),
+            Err(ShamirError::SecretOutOfRange)
+        );
+        assert_eq!(
+            generate_shares(&secret, 4, 5),
+            Err(ShamirError::InvalidThreshold(5))
+        );
+    }
 }
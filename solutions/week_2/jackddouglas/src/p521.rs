@@ -0,0 +1,270 @@
+use num_bigint::BigUint;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::PRIME;
+
+/// Number of 64-bit limbs needed for 521 bits
+const LIMBS: usize = 9;
+
+/// Bits used in the top limb (521 - 8 * 64)
+const TOP_BITS: u32 = 9;
+
+/// Mask for the top limb
+const TOP_MASK: u64 = (1 << TOP_BITS) - 1;
+
+/// An element of the field modulo the Mersenne prime p = 2^521 - 1.
+///
+/// The value is stored in nine little-endian 64-bit limbs and is always fully reduced into
+/// [0, p). Since 2^521 = 1 mod p, reduction is a shift and an add: the bits above 521 are added
+/// back onto the low bits. None of the arithmetic branches on or indexes by the value.
+#[derive(Clone, Copy, Debug)]
+pub struct Fe([u64; LIMBS]);
+
+impl Fe {
+    pub const ZERO: Fe = Fe([0; LIMBS]);
+    pub const ONE: Fe = Fe([1, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    /// Creates a field element from a BigUint, reducing it modulo p
+    ///
+    /// # Arguments
+    /// * `value` - The integer to convert
+    ///
+    /// # Returns
+    /// The field element value mod p
+    pub fn from_biguint(value: &BigUint) -> Fe {
+        let digits = (value % &*PRIME).to_u64_digits();
+        let mut limbs = [0; LIMBS];
+        limbs[..digits.len()].copy_from_slice(&digits);
+        Fe(limbs)
+    }
+
+    /// Converts the field element back into a BigUint in [0, p)
+    pub fn to_biguint(self) -> BigUint {
+        let bytes: Vec<u8> = self.0.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        BigUint::from_bytes_le(&bytes)
+    }
+
+    /// Folds the bits above 2^521 back onto the low bits. The input may use the whole top limb,
+    /// the output is below 2^521 + 2^55.
+    fn fold(mut limbs: [u64; LIMBS]) -> [u64; LIMBS] {
+        let mut carry = limbs[LIMBS - 1] >> TOP_BITS;
+        limbs[LIMBS - 1] &= TOP_MASK;
+        for limb in limbs.iter_mut() {
+            let (sum, overflow) = limb.overflowing_add(carry);
+            *limb = sum;
+            carry = overflow as u64;
+        }
+        limbs
+    }
+
+    /// Fully reduces a value below 2^576 into [0, p)
+    fn canonical(limbs: [u64; LIMBS]) -> Fe {
+        // Two folds leave a value of at most 2^521 - 1 = p
+        let limbs = Fe::fold(Fe::fold(limbs));
+
+        // v >= p exactly when v + 1 reaches 2^521; then v - p = (v + 1) - 2^521
+        let mut plus_one = [0; LIMBS];
+        let mut carry = 1;
+        for (out, limb) in plus_one.iter_mut().zip(limbs.iter()) {
+            let (sum, overflow) = limb.overflowing_add(carry);
+            *out = sum;
+            carry = overflow as u64;
+        }
+        let reduce_mask = 0u64.wrapping_sub(plus_one[LIMBS - 1] >> TOP_BITS);
+        plus_one[LIMBS - 1] &= TOP_MASK;
+
+        let mut result = [0; LIMBS];
+        for i in 0..LIMBS {
+            result[i] = (plus_one[i] & reduce_mask) | (limbs[i] & !reduce_mask);
+        }
+        Fe(result)
+    }
+
+    /// Adds two values below 2^521 without reducing; the sum fits in the top limb
+    fn add_unreduced(&self, other: &Fe) -> [u64; LIMBS] {
+        let mut limbs = [0; LIMBS];
+        let mut carry = 0;
+        for (out, (a, b)) in limbs.iter_mut().zip(self.0.iter().zip(other.0.iter())) {
+            let (sum, overflow1) = a.overflowing_add(*b);
+            let (sum, overflow2) = sum.overflowing_add(carry);
+            *out = sum;
+            carry = (overflow1 | overflow2) as u64;
+        }
+        limbs
+    }
+
+    /// Squares the field element
+    pub fn square(&self) -> Fe {
+        *self * *self
+    }
+
+    /// Computes the inverse as self^(p - 2) by Fermat's little theorem. The inverse of zero is zero.
+    ///
+    /// p - 2 = 2^521 - 3 has every bit set except bit 1, so the exponentiation always runs the
+    /// same 520 squarings and 519 multiplications.
+    pub fn invert(&self) -> Fe {
+        let mut result = *self;
+        for bit in (0..520).rev() {
+            result = result.square();
+            if bit != 1 {
+                result = result * *self;
+            }
+        }
+        result
+    }
+}
+
+impl PartialEq for Fe {
+    fn eq(&self, other: &Fe) -> bool {
+        // Both sides are canonical, so equal values have equal limbs
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+    }
+}
+
+impl Eq for Fe {}
+
+impl Add for Fe {
+    type Output = Fe;
+
+    fn add(self, other: Fe) -> Fe {
+        Fe::canonical(self.add_unreduced(&other))
+    }
+}
+
+impl Neg for Fe {
+    type Output = Fe;
+
+    fn neg(self) -> Fe {
+        // p - a flips all 521 bits of a, and p itself reduces to zero
+        let mut limbs = self.0.map(|limb| !limb);
+        limbs[LIMBS - 1] &= TOP_MASK;
+        Fe::canonical(limbs)
+    }
+}
+
+impl Sub for Fe {
+    type Output = Fe;
+
+    fn sub(self, other: Fe) -> Fe {
+        self + (-other)
+    }
+}
+
+impl Mul for Fe {
+    type Output = Fe;
+
+    fn mul(self, other: Fe) -> Fe {
+        // Schoolbook product into 18 limbs
+        let mut product = [0u64; 2 * LIMBS];
+        for i in 0..LIMBS {
+            let mut carry: u128 = 0;
+            for j in 0..LIMBS {
+                let t = product[i + j] as u128 + self.0[i] as u128 * other.0[j] as u128 + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + LIMBS] = carry as u64;
+        }
+
+        // product = high * 2^521 + low = high + low mod p
+        let mut low = [0; LIMBS];
+        low.copy_from_slice(&product[..LIMBS]);
+        low[LIMBS - 1] &= TOP_MASK;
+        let mut high = [0; LIMBS];
+        for k in 0..LIMBS {
+            high[k] =
+                (product[k + LIMBS - 1] >> TOP_BITS) | (product[k + LIMBS] << (64 - TOP_BITS));
+        }
+        Fe::canonical(Fe(low).add_unreduced(&Fe(high)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::RandBigInt;
+    use num_traits::{One, Zero};
+    use rand::thread_rng;
+
+    /// Values around the limb and modulus boundaries, plus a few random ones
+    fn samples() -> Vec<BigUint> {
+        let p = &*PRIME;
+        let mut values = vec![
+            BigUint::zero(),
+            BigUint::one(),
+            BigUint::from(2u32),
+            BigUint::from(u64::MAX),
+            BigUint::one() << 64u32,
+            BigUint::one() << 520u32,
+            (BigUint::one() << 520u32) - 1u32,
+            p - 2u32,
+            p - 1u32,
+        ];
+        let mut rng = thread_rng();
+        values.extend((0..8).map(|_| rng.gen_biguint_below(p)));
+        values
+    }
+
+    #[test]
+    fn conversion_reduces_modulo_p() {
+        let p = &*PRIME;
+        for value in [p.clone(), p + 1u32, (p << 3u32) + 5u32, BigUint::one() << 600u32] {
+            assert_eq!(Fe::from_biguint(&value).to_biguint(), &value % p);
+        }
+        for value in samples() {
+            assert_eq!(Fe::from_biguint(&value).to_biguint(), value);
+        }
+    }
+
+    #[test]
+    fn canonical_matches_biguint() {
+        let p = &*PRIME;
+        // p itself, all 521 bits set plus one, and the largest value canonical accepts
+        let inputs = [
+            p.clone(),
+            p + 1u32,
+            (BigUint::one() << 521u32) + p,
+            (BigUint::one() << 576u32) - 1u32,
+        ];
+        for value in inputs {
+            let mut limbs = [0; LIMBS];
+            let digits = value.to_u64_digits();
+            limbs[..digits.len()].copy_from_slice(&digits);
+            assert_eq!(Fe::canonical(limbs).to_biguint(), &value % p);
+        }
+    }
+
+    #[test]
+    fn arithmetic_matches_biguint() {
+        let p = &*PRIME;
+        let values = samples();
+        for a in &values {
+            let fa = Fe::from_biguint(a);
+            assert_eq!((-fa).to_biguint(), (p - a) % p);
+            for b in &values {
+                let fb = Fe::from_biguint(b);
+                assert_eq!((fa + fb).to_biguint(), (a + b) % p);
+                assert_eq!((fa - fb).to_biguint(), (a + p - b) % p);
+                assert_eq!((fa * fb).to_biguint(), (a * b) % p);
+            }
+        }
+    }
+
+    #[test]
+    fn invert_matches_fermat() {
+        let p = &*PRIME;
+        let exponent = p - 2u32;
+        for a in samples() {
+            let inverse = Fe::from_biguint(&a).invert();
+            assert_eq!(inverse.to_biguint(), a.modpow(&exponent, p));
+            if !a.is_zero() {
+                assert_eq!(inverse * Fe::from_biguint(&a), Fe::ONE);
+            }
+        }
+        assert_eq!(Fe::ZERO.invert(), Fe::ZERO);
+    }
+}
@@ -0,0 +1,273 @@
+use num_bigint::{BigInt, BigUint, ToBigInt};
+use num_traits::{One, Zero};
+use std::fmt;
+
+use crate::{mod_inverse, PRIME, PRIME_INT};
+
+/// Error returned when the shares can't be decoded.
+#[derive(Debug, PartialEq)]
+pub enum RobustError {
+    NotEnoughShares { have: usize, need: usize },
+    TooManyErrors { correctable: usize },
+    /// The threshold is zero, which no sharing can have
+    InvalidThreshold(usize),
+    /// A share ID is zero, that share would be the secret itself
+    ZeroIndex,
+    DuplicateIndex(BigUint),
+    /// A share ID or value is not below the prime, so it is from a different field
+    ValueOutOfRange(BigUint),
+}
+
+impl fmt::Display for RobustError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RobustError::NotEnoughShares { have, need } => {
+                write!(f, "{} shares given but {} are needed", have, need)
+            }
+            RobustError::TooManyErrors { correctable } => {
+                write!(f, "more than {} shares are faulty", correctable)
+            }
+            RobustError::InvalidThreshold(threshold) => {
+                write!(f, "invalid threshold {}", threshold)
+            }
+            RobustError::ZeroIndex => write!(f, "a share has ID 0"),
+            RobustError::DuplicateIndex(x) => write!(f, "more than one share has ID {}", x),
+            RobustError::ValueOutOfRange(value) => {
+                write!(f, "share value {} is not below the prime", value)
+            }
+        }
+    }
+}
+
+/// Reduces a value into the range [0, prime).
+fn reduce(value: BigInt, prime: &BigInt) -> BigInt {
+    ((value % prime) + prime) % prime
+}
+
+/// Evaluates a polynomial with coefficients in ascending order using Horner's rule.
+///
+/// # Arguments
+/// * `coefficients` - The coefficients, lowest degree first
+/// * `x` - The point to evaluate at
+/// * `prime` - The field modulus
+///
+/// # Returns
+/// The value of the polynomial at x
+fn evaluate(coefficients: &[BigInt], x: &BigInt, prime: &BigInt) -> BigInt {
+    coefficients
+        .iter()
+        .rev()
+        .fold(BigInt::zero(), |y, coefficient| (y * x + coefficient) % prime)
+}
+
+/// Solves `matrix * unknowns = rhs` modulo prime with Gauss-Jordan elimination.
+///
+/// # Arguments
+/// * `matrix` - The coefficient matrix, one row per equation
+/// * `rhs` - The right-hand side
+/// * `prime` - The field modulus
+///
+/// # Returns
+/// A solution with free unknowns set to zero, or None if the system is inconsistent
+fn solve(mut matrix: Vec<Vec<BigInt>>, mut rhs: Vec<BigInt>, prime: &BigInt) -> Option<Vec<BigInt>> {
+    let rows = matrix.len();
+    let columns = matrix[0].len();
+    let mut pivots = Vec::new();
+    let mut row = 0;
+
+    for column in 0..columns {
+        if row == rows {
+            break;
+        }
+        let pivot = match (row..rows).find(|&r| !matrix[r][column].is_zero()) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        matrix.swap(row, pivot);
+        rhs.swap(row, pivot);
+
+        let inverse = mod_inverse(&matrix[row][column], prime)?;
+        for entry in matrix[row].iter_mut() {
+            *entry = (&*entry * &inverse) % prime;
+        }
+        rhs[row] = (&rhs[row] * &inverse) % prime;
+
+        let pivot_row = matrix[row].clone();
+        for other in 0..rows {
+            if other != row && !matrix[other][column].is_zero() {
+                let factor = matrix[other][column].clone();
+                for (entry, pivot_entry) in matrix[other].iter_mut().zip(&pivot_row) {
+                    *entry = reduce(&*entry - &factor * pivot_entry, prime);
+                }
+                rhs[other] = reduce(&rhs[other] - &factor * &rhs[row], prime);
+            }
+        }
+        pivots.push(column);
+        row += 1;
+    }
+
+    if rhs[row..].iter().any(|value| !value.is_zero()) {
+        return None;
+    }
+    let mut solution = vec![BigInt::zero(); columns];
+    for (r, &column) in pivots.iter().enumerate() {
+        solution[column] = rhs[r].clone();
+    }
+    Some(solution)
+}
+
+/// Reconstructs the secret with Berlekamp-Welch decoding, correcting up to (n - t) / 2 bad shares.
+///
+/// The shares are treated as a Reed-Solomon codeword. With e = (n - t) / 2, we solve for a monic
+/// error locator E of degree e and Q of degree < e + t such that Q(x_i) = y_i * E(x_i) for every
+/// share. The share polynomial is then P = Q / E, and the faulty shares are those with P(x_i) != y_i.
+///
+/// # Arguments
+/// * `shares` - A slice of tuples, where each tuple contains a share ID and its corresponding value
+/// * `threshold` - The threshold used when the shares were generated
+///
+/// # Returns
+/// The reconstructed secret and the IDs of the faulty shares, or an error if the threshold is
+/// zero, there are too few shares, an ID is zero or repeated, an ID or value is not below the
+/// prime, or too many shares are faulty
+pub fn robust_reconstruct(
+    shares: &[(BigUint, BigUint)],
+    threshold: usize,
+) -> Result<(BigUint, Vec<BigUint>), RobustError> {
+    let prime = &*PRIME_INT;
+    if threshold == 0 {
+        return Err(RobustError::InvalidThreshold(threshold));
+    }
+    let n = shares.len();
+    if n < threshold {
+        return Err(RobustError::NotEnoughShares {
+            have: n,
+            need: threshold,
+        });
+    }
+
+    for (i, (x, y)) in shares.iter().enumerate() {
+        if let Some(value) = [x, y].into_iter().find(|value| **value >= *PRIME) {
+            return Err(RobustError::ValueOutOfRange(value.clone()));
+        }
+        if x.is_zero() {
+            return Err(RobustError::ZeroIndex);
+        }
+        if shares[..i].iter().any(|(other, _)| other == x) {
+            return Err(RobustError::DuplicateIndex(x.clone()));
+        }
+    }
+    let points: Vec<(BigInt, BigInt)> = shares
+        .iter()
+        .map(|(x, y)| (x.to_bigint().unwrap(), y.to_bigint().unwrap()))
+        .collect();
+    let correctable = (n - threshold) / 2;
+    let too_many = || RobustError::TooManyErrors { correctable };
+
+    // Unknowns are q_0..q_{e+t-1} followed by e_0..e_{e-1}, since E is monic
+    let q_len = correctable + threshold;
+    let mut matrix = Vec::with_capacity(n);
+    let mut rhs = Vec::with_capacity(n);
+    for (x, y) in &points {
+        let mut powers = vec![BigInt::one()];
+        for i in 1..=q_len {
+            powers.push((&powers[i - 1] * x) % prime);
+        }
+        let mut row = powers[..q_len].to_vec();
+        for power in &powers[..correctable] {
+            row.push(reduce(-(y * power), prime));
+        }
+        matrix.push(row);
+        rhs.push((y * &powers[correctable]) % prime);
+    }
+
+    let solution = solve(matrix, rhs, prime).ok_or_else(too_many)?;
+    let mut remainder = solution[..q_len].to_vec();
+    let mut locator = solution[q_len..].to_vec();
+    locator.push(BigInt::one());
+
+    // Long division of Q by the monic E
+    let mut polynomial = vec![BigInt::zero(); threshold];
+    for i in (0..threshold).rev() {
+        let coefficient = remainder[i + correctable].clone();
+        for (j, e) in locator.iter().enumerate() {
+            remainder[i + j] = reduce(&remainder[i + j] - &coefficient * e, prime);
+        }
+        polynomial[i] = coefficient;
+    }
+    if remainder.iter().any(|r| !r.is_zero()) {
+        return Err(too_many());
+    }
+
+    let faulty: Vec<BigUint> = shares
+        .iter()
+        .zip(&points)
+        .filter(|(_, (x, y))| evaluate(&polynomial, x, prime) != *y)
+        .map(|((id, _), _)| id.clone())
+        .collect();
+    if faulty.len() > correctable {
+        return Err(too_many());
+    }
+
+    Ok((polynomial[0].to_biguint().unwrap(), faulty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_shares;
+
+    #[test]
+    fn corrects_faulty_shares() {
+        let secret = BigUint::from(987654321u32);
+        let mut shares = generate_shares(&secret, 9, 4).unwrap();
+        assert_eq!(robust_reconstruct(&shares, 4), Ok((secret.clone(), vec![])));
+
+        shares[0].1 += 5u32;
+        shares[7].1 = BigUint::zero();
+        assert_eq!(
+            robust_reconstruct(&shares, 4),
+            Ok((secret, vec![BigUint::from(1u32), BigUint::from(8u32)]))
+        );
+
+        shares[3].1 += 1u32;
+        assert_eq!(
+            robust_reconstruct(&shares, 4),
+            Err(RobustError::TooManyErrors { correctable: 2 })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        let shares = generate_shares(&BigUint::from(7u32), 5, 3).unwrap();
+        assert_eq!(robust_reconstruct(&shares, 0), Err(RobustError::InvalidThreshold(0)));
+        assert_eq!(
+            robust_reconstruct(&[], 3),
+            Err(RobustError::NotEnoughShares { have: 0, need: 3 })
+        );
+
+        let mut repeated = shares.clone();
+        repeated[3] = repeated[1].clone();
+        assert_eq!(
+            robust_reconstruct(&repeated, 3),
+            Err(RobustError::DuplicateIndex(BigUint::from(2u32)))
+        );
+        let mut zero = shares.clone();
+        zero[2].0 = BigUint::zero();
+        assert_eq!(robust_reconstruct(&zero, 3), Err(RobustError::ZeroIndex));
+
+        // IDs and values are not reduced, so one at or above the prime is an error
+        let mut unreduced = shares.clone();
+        unreduced[2].0 = PRIME.clone();
+        assert_eq!(
+            robust_reconstruct(&unreduced, 3),
+            Err(RobustError::ValueOutOfRange(PRIME.clone()))
+        );
+        let mut unreduced = shares;
+        unreduced[4].1 += &*PRIME;
+        assert_eq!(
+            robust_reconstruct(&unreduced, 3),
+            Err(RobustError::ValueOutOfRange(unreduced[4].1.clone()))
+        );
+    }
+}
@@ -1,5 +1,7 @@
 // main.rs
 
+mod threshold_elgamal;
+
 use num_bigint::{BigInt, RandBigInt, ToBigInt};
 use num_traits::{One, Zero};
 use rand::thread_rng;
@@ -79,7 +81,9 @@ fn main() {
     println!("  Received ciphertext: {:?}", c_b);
     println!("  Decrypted message m_b: {}\n", m_b);
 
-    println!("Receiver successfully obtained message m{}: {}", b, m_b);
+    println!("Receiver successfully obtained message m{}: {}\n", b, m_b);
+
+    threshold_elgamal::run_demo();
 }
 
 // Function to generate a large prime number (for demonstration purposes, we use a fixed prime)
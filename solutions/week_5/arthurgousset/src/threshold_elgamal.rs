@@ -0,0 +1,364 @@
+// threshold_elgamal.rs
+//
+// ElGamal decryption split between trustees. The private key x is Shamir-shared, every trustee
+// publishes c1^{x_i} with a Chaum-Pedersen proof that it used the same x_i as in its public
+// verification key g^{x_i}, and any t valid decryption shares give c1^x by Lagrange
+// interpolation in the exponent. Nobody ever holds x.
+
+use std::fmt;
+
+use num_bigint::{BigInt, RandBigInt, Sign, ToBigInt};
+use num_traits::{One, Zero};
+use rand::thread_rng;
+use sha2::{Digest, Sha256};
+
+use crate::{encrypt, modinv};
+
+// The subgroup of prime order q = (p - 1) / 2 of the 2048-bit MODP group (RFC 3526, group 14).
+// Proofs need a prime-order group, so g = 4 generates the quadratic residues.
+pub struct Group {
+    pub p: BigInt,
+    pub q: BigInt,
+    pub g: BigInt,
+}
+
+impl Group {
+    pub fn modp_2048() -> Group {
+        let p = BigInt::parse_bytes(b"FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD1\
+                                      29024E088A67CC74020BBEA63B139B22514A08798E3404DD\
+                                      EF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245\
+                                      E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED\
+                                      EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3D\
+                                      C2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F\
+                                      83655D23DCA3AD961C62F356208552BB9ED529077096966D\
+                                      670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B\
+                                      E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9\
+                                      DE2BCBF6955817183995497CEA956AE515D2261898FA0510\
+                                      15728E5A8AACAA68FFFFFFFFFFFFFFFF", 16).unwrap();
+        let q = (&p - BigInt::one()) / 2;
+        Group { p, q, g: 4.to_bigint().unwrap() }
+    }
+}
+
+// A trustee's share of the private key and its g^{x_i}, kept by the trustee alone
+pub struct Trustee {
+    pub index: u64,
+    pub key_share: BigInt,
+    pub verification_key: BigInt,
+}
+
+// What everyone knows about a trustee: the g^{x_i} its proofs are checked against and the number
+// of trustees needed to decrypt, fixed when the key is generated
+#[derive(Clone, Debug)]
+pub struct PublicTrustee {
+    pub index: u64,
+    pub threshold: usize,
+    pub verification_key: BigInt,
+}
+
+// Proof that log_g(verification_key) == log_c1(d): commitments g^w and c1^w, response w + e * x_i
+#[derive(Clone, Debug)]
+pub struct ChaumPedersenProof {
+    pub a1: BigInt,
+    pub a2: BigInt,
+    pub response: BigInt,
+}
+
+// A trustee's decryption share d = c1^{x_i} for one ciphertext
+#[derive(Clone, Debug)]
+pub struct DecryptionShare {
+    pub index: u64,
+    pub d: BigInt,
+    pub proof: ChaumPedersenProof,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecryptionError {
+    // Fewer than `threshold` shares came with a valid proof; `rejected` lists the failing trustees
+    NotEnoughValidShares {
+        valid: usize,
+        threshold: usize,
+        rejected: Vec<u64>,
+    },
+    DuplicateTrustee(u64),
+    // The trustees are missing or were not generated together, so there is no single threshold
+    InconsistentTrustees,
+    // An inverse mod p or q does not exist, which valid shares of a valid ciphertext never cause
+    NotInvertible,
+}
+
+impl fmt::Display for DecryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecryptionError::NotEnoughValidShares { valid, threshold, rejected } => write!(
+                f,
+                "Only {} of the needed {} decryption shares are valid, rejected trustees {:?}",
+                valid, threshold, rejected
+            ),
+            DecryptionError::DuplicateTrustee(index) => {
+                write!(f, "Trustee {} sent more than one decryption share", index)
+            }
+            DecryptionError::InconsistentTrustees => {
+                write!(f, "The trustees do not agree on a threshold")
+            }
+            DecryptionError::NotInvertible => write!(f, "No modular inverse exists"),
+        }
+    }
+}
+
+impl std::error::Error for DecryptionError {}
+
+// Function to evaluate a polynomial mod q with Horner's rule
+fn evaluate_polynomial(coefficients: &[BigInt], x: &BigInt, q: &BigInt) -> BigInt {
+    coefficients
+        .iter()
+        .rev()
+        .fold(BigInt::zero(), |acc, coefficient| (acc * x + coefficient) % q)
+}
+
+// Function to generate the key without a dealer: every trustee shares a random z_j of its own,
+// and trustee i adds up the shares it receives, so x_i = sum_j f_j(i) is a share of x = sum_j z_j.
+// Returns the public key h = g^x, the public trustee information and the trustees' private shares.
+pub fn generate_key_shares(
+    group: &Group,
+    threshold: usize,
+    num_trustees: usize,
+) -> (BigInt, Vec<PublicTrustee>, Vec<Trustee>) {
+    assert!(threshold >= 1 && threshold <= num_trustees, "Need 1 <= threshold <= trustees");
+    let mut rng = thread_rng();
+
+    let mut public_key = BigInt::one();
+    let mut key_shares = vec![BigInt::zero(); num_trustees];
+    for _ in 0..num_trustees {
+        let coefficients: Vec<BigInt> =
+            (0..threshold).map(|_| rng.gen_bigint_range(&BigInt::zero(), &group.q)).collect();
+        // Only g^{z_j} is published, z_j itself never leaves its trustee
+        public_key = (public_key * group.g.modpow(&coefficients[0], &group.p)) % &group.p;
+        for (i, key_share) in key_shares.iter_mut().enumerate() {
+            let x = (i + 1).to_bigint().unwrap();
+            *key_share = (&*key_share + evaluate_polynomial(&coefficients, &x, &group.q)) % &group.q;
+        }
+    }
+
+    let trustees: Vec<Trustee> = key_shares
+        .into_iter()
+        .enumerate()
+        .map(|(i, key_share)| Trustee {
+            index: i as u64 + 1,
+            verification_key: group.g.modpow(&key_share, &group.p),
+            key_share,
+        })
+        .collect();
+    let public_trustees = trustees
+        .iter()
+        .map(|trustee| PublicTrustee {
+            index: trustee.index,
+            threshold,
+            verification_key: trustee.verification_key.clone(),
+        })
+        .collect();
+    (public_key, public_trustees, trustees)
+}
+
+// Fiat-Shamir challenge: hash everything the verifier sees, reduced mod q
+fn challenge(group: &Group, values: &[&BigInt]) -> BigInt {
+    let mut hasher = Sha256::new();
+    for value in [&group.p, &group.g].into_iter().chain(values.iter().copied()) {
+        let bytes = value.to_bytes_be().1;
+        hasher.update((bytes.len() as u64).to_be_bytes());
+        hasher.update(bytes);
+    }
+    BigInt::from_bytes_be(Sign::Plus, &hasher.finalize()) % &group.q
+}
+
+// Function for a trustee to compute its decryption share c1^{x_i} and prove it correct
+pub fn decryption_share(group: &Group, trustee: &Trustee, ciphertext: &(BigInt, BigInt)) -> DecryptionShare {
+    let c1 = &ciphertext.0;
+    let d = c1.modpow(&trustee.key_share, &group.p);
+
+    let w = thread_rng().gen_bigint_range(&BigInt::zero(), &group.q);
+    let a1 = group.g.modpow(&w, &group.p);
+    let a2 = c1.modpow(&w, &group.p);
+    let e = challenge(group, &[&trustee.verification_key, c1, &d, &a1, &a2]);
+    let response = (w + e * &trustee.key_share) % &group.q;
+
+    DecryptionShare {
+        index: trustee.index,
+        d,
+        proof: ChaumPedersenProof { a1, a2, response },
+    }
+}
+
+// Function to check whether a value is in the order-q subgroup, i.e. 0 < v < p and v^q == 1.
+// Since p = 3 mod 4, -1 is not a square, so this rejects -d, which would otherwise pass the
+// proof whenever the challenge is even.
+fn in_subgroup(group: &Group, value: &BigInt) -> bool {
+    value > &BigInt::zero() && value < &group.p && value.modpow(&group.q, &group.p).is_one()
+}
+
+// Function to check a decryption share: every value is in the subgroup, g^r == a1 * h_i^e and
+// c1^r == a2 * d^e
+pub fn verify_decryption_share(
+    group: &Group,
+    verification_key: &BigInt,
+    ciphertext: &(BigInt, BigInt),
+    share: &DecryptionShare,
+) -> bool {
+    let c1 = &ciphertext.0;
+    let ChaumPedersenProof { a1, a2, response } = &share.proof;
+    if ![verification_key, c1, &share.d, a1, a2].into_iter().all(|value| in_subgroup(group, value)) {
+        return false;
+    }
+    if response.sign() == Sign::Minus || response >= &group.q {
+        return false;
+    }
+    let e = challenge(group, &[verification_key, c1, &share.d, a1, a2]);
+    group.g.modpow(response, &group.p) == (a1 * verification_key.modpow(&e, &group.p)) % &group.p
+        && c1.modpow(response, &group.p) == (a2 * share.d.modpow(&e, &group.p)) % &group.p
+}
+
+// Function to compute the Lagrange coefficients at 0 mod q for distinct trustee indices
+fn lagrange_coefficients(indices: &[u64], q: &BigInt) -> Result<Vec<BigInt>, DecryptionError> {
+    indices
+        .iter()
+        .map(|&i| {
+            let mut numerator = BigInt::one();
+            let mut denominator = BigInt::one();
+            for &j in indices.iter().filter(|&&j| j != i) {
+                numerator = (numerator * j) % q;
+                denominator = (denominator * (j.to_bigint().unwrap() - i) % q + q) % q;
+            }
+            let inverse = modinv(&denominator, q).ok_or(DecryptionError::NotInvertible)?;
+            Ok((numerator * inverse) % q)
+        })
+        .collect()
+}
+
+// Function to decrypt with any `threshold` shares that carry a valid proof, where the threshold
+// is the one the trustees' key was generated for: c1^x = prod d_i^{l_i}, then m = c2 / c1^x as
+// in plain ElGamal. Only public trustee information is needed, never a key share.
+pub fn threshold_decrypt(
+    group: &Group,
+    trustees: &[PublicTrustee],
+    ciphertext: &(BigInt, BigInt),
+    shares: &[DecryptionShare],
+) -> Result<BigInt, DecryptionError> {
+    let threshold = trustees.first().ok_or(DecryptionError::InconsistentTrustees)?.threshold;
+    if threshold == 0 || trustees.iter().any(|trustee| trustee.threshold != threshold) {
+        return Err(DecryptionError::InconsistentTrustees);
+    }
+
+    let mut valid: Vec<&DecryptionShare> = Vec::new();
+    let mut rejected = Vec::new();
+    for (n, share) in shares.iter().enumerate() {
+        // A second share from the same trustee is refused even if the first one was invalid
+        if shares[..n].iter().any(|other| other.index == share.index) {
+            return Err(DecryptionError::DuplicateTrustee(share.index));
+        }
+        let trustee = trustees.iter().find(|trustee| trustee.index == share.index);
+        match trustee {
+            Some(trustee) if verify_decryption_share(group, &trustee.verification_key, ciphertext, share) => {
+                valid.push(share)
+            }
+            _ => rejected.push(share.index),
+        }
+    }
+    if valid.len() < threshold {
+        return Err(DecryptionError::NotEnoughValidShares { valid: valid.len(), threshold, rejected });
+    }
+
+    let valid = &valid[..threshold];
+    let indices: Vec<u64> = valid.iter().map(|share| share.index).collect();
+    let shared_secret = valid
+        .iter()
+        .zip(lagrange_coefficients(&indices, &group.q)?)
+        .fold(BigInt::one(), |acc, (share, l)| (acc * share.d.modpow(&l, &group.p)) % &group.p);
+
+    let (_, c2) = ciphertext;
+    let inverse = modinv(&shared_secret, &group.p).ok_or(DecryptionError::NotInvertible)?;
+    Ok((c2 * inverse) % &group.p)
+}
+
+pub fn run_demo() {
+    println!("--- Threshold ElGamal decryption (3 of 5 trustees) ---\n");
+
+    let group = Group::modp_2048();
+    let (public_key, public_trustees, trustees) = generate_key_shares(&group, 3, 5);
+    println!("Joint public key (h): {}\n", public_key);
+
+    // A ballot encrypted to the trustees' joint key, with the existing ElGamal encryption
+    let ballot = 4.to_bigint().unwrap(); // g^1, a "yes" vote
+    let k = thread_rng().gen_bigint_range(&BigInt::one(), &group.q);
+    let ciphertext = encrypt(&ballot, &public_key, &k, &group.g, &group.p);
+
+    // Trustees 1, 3, 4 and 5 answer; trustee 3 publishes a wrong share with a made-up proof
+    let mut shares: Vec<DecryptionShare> = [0, 2, 3, 4]
+        .iter()
+        .map(|&i| decryption_share(&group, &trustees[i], &ciphertext))
+        .collect();
+    shares[1].d = (&shares[1].d * &group.g) % &group.p;
+
+    for share in &shares {
+        let trustee = &public_trustees[share.index as usize - 1];
+        println!(
+            "Trustee {} decryption share valid: {}",
+            share.index,
+            verify_decryption_share(&group, &trustee.verification_key, &ciphertext, share)
+        );
+    }
+
+    match threshold_decrypt(&group, &public_trustees, &ciphertext, &shares) {
+        Ok(m) => println!("\nDecrypted ballot: {} (matches: {})", m, m == ballot),
+        Err(error) => println!("\nDecryption failed: {}", error),
+    }
+    if let Err(error) = threshold_decrypt(&group, &public_trustees, &ciphertext, &shares[..3]) {
+        println!("With trustees 1, 3 and 4 only: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negated_share_is_rejected() {
+        let group = Group::modp_2048();
+        let (public_key, public_trustees, trustees) = generate_key_shares(&group, 2, 3);
+        let k = thread_rng().gen_bigint_range(&BigInt::one(), &group.q);
+        let ballot = 16.to_bigint().unwrap();
+        let ciphertext = encrypt(&ballot, &public_key, &k, &group.g, &group.p);
+        let trustee = &trustees[0];
+        let honest = decryption_share(&group, trustee, &ciphertext);
+        assert!(verify_decryption_share(&group, &trustee.verification_key, &ciphertext, &honest));
+
+        // A cheating trustee publishes p - d and retries its nonce until the challenge is even,
+        // so (-d)^e == d^e and the Chaum-Pedersen equations still hold
+        let c1 = &ciphertext.0;
+        let negated_d = &group.p - &honest.d;
+        let negated = loop {
+            let w = thread_rng().gen_bigint_range(&BigInt::zero(), &group.q);
+            let a1 = group.g.modpow(&w, &group.p);
+            let a2 = c1.modpow(&w, &group.p);
+            let e = challenge(&group, &[&trustee.verification_key, c1, &negated_d, &a1, &a2]);
+            if (&e % 2u32).is_zero() {
+                let response = (w + e * &trustee.key_share) % &group.q;
+                let proof = ChaumPedersenProof { a1, a2, response };
+                break DecryptionShare { index: trustee.index, d: negated_d.clone(), proof };
+            }
+        };
+        assert!(!verify_decryption_share(&group, &trustee.verification_key, &ciphertext, &negated));
+
+        // Decryption skips it, and a second share from the same trustee is refused
+        let other = decryption_share(&group, &trustees[1], &ciphertext);
+        assert_eq!(
+            threshold_decrypt(&group, &public_trustees, &ciphertext, &[negated.clone(), other.clone()]),
+            Err(DecryptionError::NotEnoughValidShares { valid: 1, threshold: 2, rejected: vec![1] })
+        );
+        let repeated = [negated, honest.clone(), other.clone()];
+        assert_eq!(
+            threshold_decrypt(&group, &public_trustees, &ciphertext, &repeated),
+            Err(DecryptionError::DuplicateTrustee(1))
+        );
+        assert_eq!(threshold_decrypt(&group, &public_trustees, &ciphertext, &[other, honest]), Ok(ballot));
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::Zero;
+use rand::rngs::OsRng;
+
+use crate::fdh::{fdh_encode, fdh_verify};
+use crate::{blind_message, generate_rsa_keys, rsa_sign, unblind_signature};
+
+/// Bit length of coin serial numbers
+const SERIAL_BITS: u64 = 256;
+
+/// Errors returned by the mint, wallets and merchants
+#[derive(Debug, PartialEq)]
+pub enum EcashError {
+    UnknownDenomination(u64),
+    UnknownAccount(String),
+    InsufficientFunds {
+        account: String,
+        balance: u64,
+        requested: u64,
+    },
+    InvalidSignature,
+    /// The blinded serial is not below the denomination modulus
+    InvalidBlindedSerial,
+    DoubleSpend(BigUint),
+}
+
+impl fmt::Display for EcashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EcashError::UnknownDenomination(value) => {
+                write!(f, "no mint key for denomination {}", value)
+            }
+            EcashError::UnknownAccount(account) => write!(f, "unknown account '{}'", account),
+            EcashError::InsufficientFunds {
+                account,
+                balance,
+                requested,
+            } => write!(
+                f,
+                "account '{}' has {} but tried to withdraw {}",
+                account, balance, requested
+            ),
+            EcashError::InvalidSignature => write!(f, "coin signature is invalid"),
+            EcashError::InvalidBlindedSerial => {
+                write!(f, "blinded serial is not below the mint modulus")
+            }
+            EcashError::DoubleSpend(serial) => write!(f, "coin {} has already been spent", serial),
+        }
+    }
+}
+
+/// Public half of a denomination key
+#[derive(Clone, Debug)]
+pub struct PublicKey {
+    pub n: BigUint,
+    pub e: BigUint,
+}
+
+/// RSA key the mint uses to sign coins of a single denomination
+struct DenominationKey {
+    public: PublicKey,
+    d: BigUint,
+}
+
+/// A coin is a serial number whose full-domain hash is signed with the key of its denomination
+#[derive(Clone, Debug)]
+pub struct Coin {
+    pub denomination: u64,
+    pub serial: BigUint,
+    pub signature: BigUint,
+}
+
+impl Coin {
+    /// Check the mint signature on the coin serial
+    pub fn verify(&self, key: &PublicKey) -> bool {
+        // Signing FDH(serial) rather than the serial stops coins being multiplied into new ones
+        fdh_verify(&self.serial.to_bytes_be(), &self.signature, &key.e, &key.n)
+    }
+}
+
+/// The bank: holds customer accounts, signs coins and redeems them exactly once
+pub struct Mint {
+    keys: HashMap<u64, DenominationKey>,
+    accounts: HashMap<String, u64>,
+    spent_serials: HashSet<(u64, BigUint)>,
+}
+
+impl Mint {
+    /// Create a mint with one RSA key per denomination
+    pub fn new(denominations: &[u64], bits: usize) -> Self {
+        let keys = denominations
+            .iter()
+            .map(|&value| {
+                let (n, e, d) = generate_rsa_keys(bits);
+                (
+                    value,
+                    DenominationKey {
+                        public: PublicKey { n, e },
+                        d,
+                    },
+                )
+            })
+            .collect();
+
+        Mint {
+            keys,
+            accounts: HashMap::new(),
+            spent_serials: HashSet::new(),
+        }
+    }
+
+    /// Public key that verifies coins of the given denomination
+    pub fn public_key(&self, denomination: u64) -> Result<&PublicKey, EcashError> {
+        self.keys
+            .get(&denomination)
+            .map(|key| &key.public)
+            .ok_or(EcashError::UnknownDenomination(denomination))
+    }
+
+    /// Open an account (or top up an existing one)
+    pub fn credit(&mut self, account: &str, amount: u64) {
+        *self.accounts.entry(account.to_string()).or_insert(0) += amount;
+    }
+
+    pub fn balance(&self, account: &str) -> u64 {
+        self.accounts.get(account).copied().unwrap_or(0)
+    }
+
+    /// Debit the account and sign a blinded serial with the denomination key.
+    ///
+    /// The mint never sees the serial itself, so it can't link the coin to the withdrawal later.
+    /// A blinded serial outside [1, n) is rejected before the account is touched.
+    pub fn withdraw(
+        &mut self,
+        account: &str,
+        denomination: u64,
+        blinded_serial: &BigUint,
+    ) -> Result<BigUint, EcashError> {
+        let key = self
+            .keys
+            .get(&denomination)
+            .ok_or(EcashError::UnknownDenomination(denomination))?;
+        if blinded_serial.is_zero() || blinded_serial >= &key.public.n {
+            return Err(EcashError::InvalidBlindedSerial);
+        }
+        let balance = self
+            .accounts
+            .get_mut(account)
+            .ok_or_else(|| EcashError::UnknownAccount(account.to_string()))?;
+
+        if *balance < denomination {
+            return Err(EcashError::InsufficientFunds {
+                account: account.to_string(),
+                balance: *balance,
+                requested: denomination,
+            });
+        }
+        *balance -= denomination;
+
+        Ok(rsa_sign(blinded_serial, &key.d, &key.public.n))
+    }
+
+    /// Redeem a coin into the depositor's account, rejecting serials that were already spent
+    pub fn deposit(&mut self, account: &str, coin: &Coin) -> Result<(), EcashError> {
+        let key = self.public_key(coin.denomination)?;
+        if !coin.verify(key) {
+            return Err(EcashError::InvalidSignature);
+        }
+
+        // The spent-serial database is what stops a coin from being copied and spent twice
+        if !self
+            .spent_serials
+            .insert((coin.denomination, coin.serial.clone()))
+        {
+            return Err(EcashError::DoubleSpend(coin.serial.clone()));
+        }
+
+        self.credit(account, coin.denomination);
+        Ok(())
+    }
+}
+
+/// A customer holding coins withdrawn from the mint
+pub struct Wallet {
+    pub owner: String,
+    pub coins: Vec<Coin>,
+}
+
+impl Wallet {
+    pub fn new(owner: &str) -> Self {
+        Wallet {
+            owner: owner.to_string(),
+            coins: Vec::new(),
+        }
+    }
+
+    /// Withdraw a coin: pick a random serial, blind it, have the mint sign it and unblind
+    pub fn withdraw(&mut self, mint: &mut Mint, denomination: u64) -> Result<(), EcashError> {
+        let key = mint.public_key(denomination)?.clone();
+
+        let serial = OsRng.gen_biguint(SERIAL_BITS);
+        let encoded_serial = fdh_encode(&serial.to_bytes_be(), &key.n);
+        let (blinded_serial, r) = blind_message(&encoded_serial, &key.e, &key.n);
+
+        let blinded_signature = mint.withdraw(&self.owner, denomination, &blinded_serial)?;
+        let signature = unblind_signature(&blinded_signature, &r, &key.n);
+
+        let coin = Coin {
+            denomination,
+            serial,
+            signature,
+        };
+        if !coin.verify(&key) {
+            return Err(EcashError::InvalidSignature);
+        }
+
+        self.coins.push(coin);
+        Ok(())
+    }
+
+    /// Sum of all coins in the wallet
+    pub fn balance(&self) -> u64 {
+        self.coins.iter().map(|coin| coin.denomination).sum()
+    }
+
+    /// Hand over a coin of the given denomination, if the wallet holds one
+    pub fn take_coin(&mut self, denomination: u64) -> Option<Coin> {
+        let index = self
+            .coins
+            .iter()
+            .position(|coin| coin.denomination == denomination)?;
+        Some(self.coins.remove(index))
+    }
+}
+
+/// A shop accepting coins and depositing them at the mint
+pub struct Merchant {
+    pub name: String,
+}
+
+impl Merchant {
+    pub fn new(name: &str) -> Self {
+        Merchant {
+            name: name.to_string(),
+        }
+    }
+
+    /// Accept a coin as payment: check the signature offline, then deposit it online
+    pub fn accept(&self, mint: &mut Mint, coin: &Coin) -> Result<(), EcashError> {
+        if !coin.verify(mint.public_key(coin.denomination)?) {
+            return Err(EcashError::InvalidSignature);
+        }
+        mint.deposit(&self.name, coin)
+    }
+}
+
+/// Simulate several wallets spending coins at several merchants, including a double-spend attempt
+pub fn run_simulation() {
+    println!("\n--- Chaumian e-cash ---\n");
+
+    let denominations = [1, 5, 10];
+    let mut mint = Mint::new(&denominations, 512);
+
+    let mut wallets = vec![
+        Wallet::new("alice"),
+        Wallet::new("bob"),
+        Wallet::new("carol"),
+    ];
+    let merchants = vec![Merchant::new("bakery"), Merchant::new("bookshop")];
+
+    for wallet in &wallets {
+        mint.credit(&wallet.owner, 20);
+    }
+
+    // Withdrawals
+    let withdrawals: [(usize, &[u64]); 3] = [(0, &[10, 5, 5]), (1, &[10, 5, 1]), (2, &[5, 1])];
+    for (index, amounts) in withdrawals {
+        let wallet = &mut wallets[index];
+        for &denomination in amounts {
+            match wallet.withdraw(&mut mint, denomination) {
+                Ok(()) => println!("{} withdrew a {} coin", wallet.owner, denomination),
+                Err(e) => println!(
+                    "{} failed to withdraw a {} coin: {}",
+                    wallet.owner, denomination, e
+                ),
+            }
+        }
+    }
+
+    // Payments
+    let payments = [(0, 0, 5), (1, 1, 10), (0, 1, 10), (2, 0, 1), (1, 0, 1)];
+    for (wallet_index, merchant_index, denomination) in payments {
+        let wallet = &mut wallets[wallet_index];
+        let merchant = &merchants[merchant_index];
+        let Some(coin) = wallet.take_coin(denomination) else {
+            println!("{} has no {} coin", wallet.owner, denomination);
+            continue;
+        };
+        match merchant.accept(&mut mint, &coin) {
+            Ok(()) => println!(
+                "{} paid {} to {}",
+                wallet.owner, denomination, merchant.name
+            ),
+            Err(e) => println!(
+                "{} rejected payment from {}: {}",
+                merchant.name, wallet.owner, e
+            ),
+        }
+    }
+
+    // Carol copies her 5 coin and tries to spend it at both merchants
+    let coin = wallets[2].take_coin(5).expect("carol withdrew a 5 coin");
+    for merchant in &merchants {
+        match merchant.accept(&mut mint, &coin) {
+            Ok(()) => println!("carol paid 5 to {}", merchant.name),
+            Err(e) => println!("{} rejected payment from carol: {}", merchant.name, e),
+        }
+    }
+
+    // A coin with a forged signature is rejected too
+    let mut forged = wallets[0].take_coin(5).expect("alice still holds a 5 coin");
+    forged.signature += 1u32;
+    if let Err(e) = merchants[1].accept(&mut mint, &forged) {
+        println!("{} rejected forged coin: {}", merchants[1].name, e);
+    }
+
+    println!("\nFinal balances:");
+    for wallet in &wallets {
+        println!(
+            "  {}: account {}, wallet {}",
+            wallet.owner,
+            mint.balance(&wallet.owner),
+            wallet.balance()
+        );
+    }
+    for merchant in &merchants {
+        println!(
+            "  {}: account {}",
+            merchant.name,
+            mint.balance(&merchant.name)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn funded_mint() -> (Mint, Wallet) {
+        let mut mint = Mint::new(&[5], 512);
+        mint.credit("alice", 10);
+        let mut wallet = Wallet::new("alice");
+        wallet.withdraw(&mut mint, 5).unwrap();
+        (mint, wallet)
+    }
+
+    #[test]
+    fn replayed_coin_is_a_double_spend() {
+        let (mut mint, mut wallet) = funded_mint();
+        let coin = wallet.take_coin(5).unwrap();
+
+        assert_eq!(mint.deposit("bakery", &coin), Ok(()));
+        assert_eq!(
+            mint.deposit("bookshop", &coin),
+            Err(EcashError::DoubleSpend(coin.serial.clone()))
+        );
+        assert_eq!(mint.balance("bakery"), 5);
+        assert_eq!(mint.balance("bookshop"), 0);
+    }
+
+    #[test]
+    fn tampered_coins_are_rejected() {
+        let (mut mint, mut wallet) = funded_mint();
+        let coin = wallet.take_coin(5).unwrap();
+
+        let mut serial = coin.clone();
+        serial.serial += 1u32;
+        assert_eq!(
+            mint.deposit("bakery", &serial),
+            Err(EcashError::InvalidSignature)
+        );
+        let mut signature = coin.clone();
+        signature.signature += 1u32;
+        assert_eq!(
+            mint.deposit("bakery", &signature),
+            Err(EcashError::InvalidSignature)
+        );
+
+        // The rejected copies did not mark the serial as spent
+        assert_eq!(mint.deposit("bakery", &coin), Ok(()));
+    }
+
+    #[test]
+    fn withdrawal_checks_funds_and_input() {
+        let (mut mint, _) = funded_mint();
+        let n = mint.public_key(5).unwrap().n.clone();
+
+        assert_eq!(
+            mint.withdraw("alice", 5, &n),
+            Err(EcashError::InvalidBlindedSerial)
+        );
+        assert_eq!(mint.balance("alice"), 5);
+
+        assert!(mint.withdraw("alice", 5, &BigUint::from(2u32)).is_ok());
+        assert_eq!(
+            mint.withdraw("alice", 5, &BigUint::from(2u32)),
+            Err(EcashError::InsufficientFunds {
+                account: "alice".to_string(),
+                balance: 0,
+                requested: 5,
+            })
+        );
+    }
+}
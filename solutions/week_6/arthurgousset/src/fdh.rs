@@ -0,0 +1,220 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::One;
+use sha2::{Digest, Sha256};
+
+use crate::{rsa_sign, rsa_verify};
+
+/// Domain separation prefix for the full-domain hash
+const FDH_LABEL: &[u8] = b"schola-rsa-fdh";
+
+/// Errors returned when turning a message into an RSA input
+#[derive(Debug, PartialEq)]
+pub enum EncodingError {
+    MessageOutOfRange {
+        message_bits: u64,
+        modulus_bits: u64,
+    },
+    TrivialMessage,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingError::MessageOutOfRange {
+                message_bits,
+                modulus_bits,
+            } => write!(
+                f,
+                "message of {} bits does not fit below a {}-bit modulus",
+                message_bits, modulus_bits
+            ),
+            EncodingError::TrivialMessage => write!(f, "messages 0 and 1 are their own signatures"),
+        }
+    }
+}
+
+/// MGF1 mask generation with SHA-256 (RFC 8017, appendix B.2.1)
+pub fn mgf1_sha256(seed: &[u8], length: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(length + 32);
+    let mut counter = 0u32;
+    while output.len() < length {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(counter.to_be_bytes());
+        output.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    output.truncate(length);
+    output
+}
+
+/// Full-domain hash: expand the message with MGF1 to |n| bits and retry until the value is below n
+pub fn fdh_encode(message: &[u8], n: &BigUint) -> BigUint {
+    let bits = n.bits();
+    let length = bits.div_ceil(8) as usize;
+    let excess_bits = length as u64 * 8 - bits;
+    let digest = Sha256::digest(message);
+
+    let mut attempt = 0u32;
+    loop {
+        let mut seed = FDH_LABEL.to_vec();
+        seed.extend_from_slice(&attempt.to_be_bytes());
+        seed.extend_from_slice(&digest);
+
+        // Clear the bits above |n| so the candidate has the same bit length as the modulus
+        let mut expanded = mgf1_sha256(&seed, length);
+        expanded[0] &= 0xff >> excess_bits;
+
+        let encoded = BigUint::from_bytes_be(&expanded);
+        if &encoded < n {
+            return encoded;
+        }
+        attempt += 1;
+    }
+}
+
+/// Raw encoding of the message bytes as an integer, rejecting values the modulus would wrap
+pub fn raw_encode(message: &[u8], n: &BigUint) -> Result<BigUint, EncodingError> {
+    let encoded = BigUint::from_bytes_be(message);
+    if &encoded >= n {
+        return Err(EncodingError::MessageOutOfRange {
+            message_bits: encoded.bits(),
+            modulus_bits: n.bits(),
+        });
+    }
+    if encoded <= BigUint::one() {
+        return Err(EncodingError::TrivialMessage);
+    }
+    Ok(encoded)
+}
+
+/// Sign the raw encoding of the message, refusing messages the modulus would wrap
+pub fn raw_sign(message: &[u8], d: &BigUint, n: &BigUint) -> Result<BigUint, EncodingError> {
+    Ok(rsa_sign(&raw_encode(message, n)?, d, n))
+}
+
+/// Verify a signature on the raw encoding of the message
+pub fn raw_verify(message: &[u8], signature: &BigUint, e: &BigUint, n: &BigUint) -> bool {
+    match raw_encode(message, n) {
+        Ok(encoded) => rsa_verify(&encoded, signature, e, n),
+        Err(_) => false,
+    }
+}
+
+/// Sign FDH(message) using private exponent d
+pub fn fdh_sign(message: &[u8], d: &BigUint, n: &BigUint) -> BigUint {
+    rsa_sign(&fdh_encode(message, n), d, n)
+}
+
+/// Verify a signature on FDH(message) using public exponent e
+pub fn fdh_verify(message: &[u8], signature: &BigUint, e: &BigUint, n: &BigUint) -> bool {
+    rsa_verify(&fdh_encode(message, n), signature, e, n)
+}
+
+/// Bytes of the message whose raw encoding is the product of the encodings of m1 and m2
+fn raw_forgery_message(m1: &[u8], m2: &[u8], n: &BigUint) -> Vec<u8> {
+    ((BigUint::from_bytes_be(m1) * BigUint::from_bytes_be(m2)) % n).to_bytes_be()
+}
+
+/// Show that the raw scheme is malleable and that FDH is not
+pub fn run_tampering_demo(n: &BigUint, e: &BigUint, d: &BigUint) {
+    println!("\n--- Raw RSA vs full-domain hash ---\n");
+
+    // Messages at least as long as the modulus used to wrap silently
+    let long_text = "Vote for Alice. ".repeat(8);
+    match raw_encode(long_text.as_bytes(), n) {
+        Ok(_) => println!("Raw encoding accepted a {}-byte message", long_text.len()),
+        Err(error) => println!(
+            "Raw encoding rejected a {}-byte message: {}",
+            long_text.len(),
+            error
+        ),
+    }
+    let signature = fdh_sign(long_text.as_bytes(), d, n);
+    println!(
+        "FDH signature on the {}-byte message valid: {}",
+        long_text.len(),
+        fdh_verify(long_text.as_bytes(), &signature, e, n)
+    );
+
+    // Raw RSA: signatures on m1 and m2 multiply into a signature on m1 * m2 nobody signed
+    let s1 = raw_sign(b"Vote", d, n).unwrap();
+    let s2 = raw_sign(b"Alice", d, n).unwrap();
+    let forged_message = raw_forgery_message(b"Vote", b"Alice", n);
+    let forged_signature = (&s1 * &s2) % n;
+    println!(
+        "Raw forgery on m1 * m2 = {} accepted: {}",
+        BigUint::from_bytes_be(&forged_message),
+        raw_verify(&forged_message, &forged_signature, e, n)
+    );
+
+    // FDH: the product of two signatures is not a signature on anything we can name
+    let s1 = fdh_sign(b"Vote", d, n);
+    let s2 = fdh_sign(b"Alice", d, n);
+    let forged_signature = (&s1 * &s2) % n;
+    let candidates: [&[u8]; 3] = [b"Vote", b"Alice", b"VoteAlice"];
+    let accepted = candidates
+        .iter()
+        .any(|message| fdh_verify(message, &forged_signature, e, n));
+    println!(
+        "FDH forgery accepted for any candidate message: {}",
+        accepted
+    );
+
+    // The same forgery works on FDH values, but inverting the hash to find a message is infeasible
+    let forged_value = (fdh_encode(b"Vote", n) * fdh_encode(b"Alice", n)) % n;
+    println!(
+        "Forged signature only verifies the unnamed value FDH(Vote) * FDH(Alice): {}",
+        rsa_verify(&forged_value, &forged_signature, e, n)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_rsa_keys;
+
+    #[test]
+    fn fdh_encoding_is_deterministic_and_below_n() {
+        let (n, _, _) = generate_rsa_keys(512);
+        for message in [&b""[..], b"Vote", b"Alice", &[0xff; 200]] {
+            let encoded = fdh_encode(message, &n);
+            assert!(encoded < n);
+            assert_eq!(encoded, fdh_encode(message, &n));
+        }
+        assert_ne!(fdh_encode(b"Vote", &n), fdh_encode(b"Alice", &n));
+    }
+
+    #[test]
+    fn raw_encoding_rejects_wrapping_and_trivial_messages() {
+        let (n, _, _) = generate_rsa_keys(512);
+        for message in [n.to_bytes_be(), (&n + 1u32).to_bytes_be(), vec![0xff; 64]] {
+            assert!(matches!(
+                raw_encode(&message, &n),
+                Err(EncodingError::MessageOutOfRange { .. })
+            ));
+        }
+        for message in [&b""[..], &[0], &[1], &[0, 1]] {
+            assert_eq!(raw_encode(message, &n), Err(EncodingError::TrivialMessage));
+        }
+        assert_eq!(raw_encode(&[2], &n), Ok(BigUint::from(2u32)));
+    }
+
+    #[test]
+    fn multiplicative_forgery_only_breaks_raw_rsa() {
+        let (n, e, d) = generate_rsa_keys(512);
+
+        let raw_forgery =
+            (raw_sign(b"Vote", &d, &n).unwrap() * raw_sign(b"Alice", &d, &n).unwrap()) % &n;
+        let forged_message = raw_forgery_message(b"Vote", b"Alice", &n);
+        assert!(raw_verify(&forged_message, &raw_forgery, &e, &n));
+        assert!(!raw_verify(&forged_message, &(&raw_forgery + &n), &e, &n));
+
+        let fdh_forgery = (fdh_sign(b"Vote", &d, &n) * fdh_sign(b"Alice", &d, &n)) % &n;
+        assert!(!fdh_verify(&forged_message, &fdh_forgery, &e, &n));
+        assert!(!fdh_verify(b"VoteAlice", &fdh_forgery, &e, &n));
+        assert!(fdh_verify(b"Vote", &fdh_sign(b"Vote", &d, &n), &e, &n));
+    }
+}
@@ -3,6 +3,10 @@ use num_integer::Integer;
 use num_traits::{One, Zero};
 use rand::rngs::OsRng;
 
+mod ecash;
+mod fdh;
+mod partially_blind;
+
 /// Entry point demonstrating RSA blind signature
 fn main() {
     // Generate RSA keys
@@ -12,8 +16,8 @@ fn main() {
     let text = "Vote for Alice";
     println!("Text: {}", text);
 
-    // Message to be signed
-    let message = BigUint::from_bytes_be(text.as_bytes());
+    // Message to be signed: the full-domain hash of the text, so it always lies below n
+    let message = fdh::fdh_encode(text.as_bytes(), &n);
     println!("Message: {}", message);
 
     // Blinding factor and blinded message
@@ -33,6 +37,15 @@ fn main() {
     println!("Expected message: {}", message);
 
     println!("Signature valid: {}", is_valid);
+
+    // Forgeries against raw RSA and why the full-domain hash stops them
+    fdh::run_tampering_demo(&n, &e, &d);
+
+    // Blind signatures put together into an e-cash protocol
+    ecash::run_simulation();
+
+    // Partially blind signatures bind public info (here an expiry epoch) into the signature
+    partially_blind::run_demo();
 }
 
 /// Generate RSA keys (n, e, d) with specified bit length
@@ -161,8 +174,9 @@ fn blind_message(message: &BigUint, e: &BigUint, n: &BigUint) -> (BigUint, BigUi
     }
 }
 
-/// Sign the message using private exponent d
+/// Sign the message using private exponent d; raw bytes go through `fdh::raw_sign` instead
 fn rsa_sign(message: &BigUint, d: &BigUint, n: &BigUint) -> BigUint {
+    assert!(message < n, "message must be reduced below the modulus");
     message.modpow(d, n)
 }
 
@@ -177,6 +191,11 @@ fn unblind_signature(blinded_signature: &BigUint, r: &BigUint, n: &BigUint) -> B
 
 /// Verify the signature using public exponent e
 fn rsa_verify(message: &BigUint, signature: &BigUint, e: &BigUint, n: &BigUint) -> bool {
+    // Unreduced values would let message + n or signature + n verify as well
+    if message >= n || signature >= n {
+        return false;
+    }
+
     // Compute expected_message = signature^e mod n
     let expected_message = signature.modpow(e, n);
 
@@ -0,0 +1,352 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::One;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+/// Prime-order group: the subgroup of quadratic residues of the 2048-bit MODP group (RFC 3526)
+pub struct Group {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+impl Group {
+    /// The MODP prime is a safe prime p = 2q + 1, so squares form a subgroup of prime order q
+    pub fn modp_2048() -> Self {
+        let p = BigUint::parse_bytes(
+            b"FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+            16,
+        )
+        .unwrap();
+        let q = (&p - BigUint::one()) >> 1;
+        let g = BigUint::from(4u32); // 2^2 is a square, so it generates the order-q subgroup
+        Group { p, q, g }
+    }
+
+    /// Random exponent in [0, q)
+    fn random_exponent(&self) -> BigUint {
+        OsRng.gen_biguint_below(&self.q)
+    }
+
+    /// Map the public info to a group element z = F(info) whose discrete log nobody knows
+    pub fn hash_info(&self, info: &[u8]) -> BigUint {
+        // Expand SHA-256 to more bits than p, then square to land in the subgroup
+        let mut expanded = Vec::new();
+        let mut counter = 0u32;
+        while expanded.len() * 8 < self.p.bits() as usize + 128 {
+            let mut hasher = Sha256::new();
+            hasher.update(b"abe-okamoto-info");
+            hasher.update(counter.to_be_bytes());
+            hasher.update(info);
+            expanded.extend_from_slice(&hasher.finalize());
+            counter += 1;
+        }
+        let z = BigUint::from_bytes_be(&expanded) % &self.p;
+        z.modpow(&BigUint::from(2u32), &self.p)
+    }
+
+    /// Challenge hash H(alpha || beta || z || message) mod q
+    fn challenge(&self, alpha: &BigUint, beta: &BigUint, z: &BigUint, message: &[u8]) -> BigUint {
+        let mut hasher = Sha256::new();
+        for element in [alpha, beta, z] {
+            let bytes = element.to_bytes_be();
+            hasher.update((bytes.len() as u32).to_be_bytes());
+            hasher.update(bytes);
+        }
+        hasher.update(message);
+        BigUint::from_bytes_be(&hasher.finalize()) % &self.q
+    }
+
+    /// (a - b) mod q for exponents already reduced mod q
+    fn sub_mod_q(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        ((a + &self.q) - (b % &self.q)) % &self.q
+    }
+}
+
+/// Signer key pair: secret x and public y = g^x
+pub struct SignerKey {
+    x: BigUint,
+    pub y: BigUint,
+}
+
+impl SignerKey {
+    pub fn generate(group: &Group) -> Self {
+        let x = group.random_exponent();
+        let y = group.g.modpow(&x, &group.p);
+        SignerKey { x, y }
+    }
+}
+
+/// First signer message: a = g^u, b = g^s z^d
+pub struct Commitment {
+    pub a: BigUint,
+    pub b: BigUint,
+}
+
+/// Randomness the signer keeps between the two rounds
+pub struct SignerState {
+    u: BigUint,
+    s: BigUint,
+    d: BigUint,
+}
+
+/// Second signer message
+pub struct Response {
+    pub r: BigUint,
+    pub c: BigUint,
+    pub s: BigUint,
+    pub d: BigUint,
+}
+
+/// Blinding values the user keeps between the two rounds
+pub struct UserState {
+    t1: BigUint,
+    t2: BigUint,
+    t3: BigUint,
+    t4: BigUint,
+}
+
+/// Partially blind signature (rho, omega, sigma, delta) on a message and public info
+#[derive(Clone, Debug)]
+pub struct Signature {
+    pub rho: BigUint,
+    pub omega: BigUint,
+    pub sigma: BigUint,
+    pub delta: BigUint,
+}
+
+/// Signer round 1: commit to fresh randomness for the agreed info
+pub fn signer_commit(group: &Group, info: &[u8]) -> (Commitment, SignerState) {
+    let z = group.hash_info(info);
+    let (u, s, d) = (
+        group.random_exponent(),
+        group.random_exponent(),
+        group.random_exponent(),
+    );
+
+    let a = group.g.modpow(&u, &group.p);
+    let b = (group.g.modpow(&s, &group.p) * z.modpow(&d, &group.p)) % &group.p;
+
+    (Commitment { a, b }, SignerState { u, s, d })
+}
+
+/// User round 1: blind the commitment and derive the challenge e = epsilon - t2 - t4
+pub fn user_blind(
+    group: &Group,
+    y: &BigUint,
+    info: &[u8],
+    message: &[u8],
+    commitment: &Commitment,
+) -> (BigUint, UserState) {
+    let p = &group.p;
+    let z = group.hash_info(info);
+    let (t1, t2, t3, t4) = (
+        group.random_exponent(),
+        group.random_exponent(),
+        group.random_exponent(),
+        group.random_exponent(),
+    );
+
+    // alpha = a g^t1 y^t2, beta = b g^t3 z^t4
+    let alpha = (&commitment.a * group.g.modpow(&t1, p) % p) * y.modpow(&t2, p) % p;
+    let beta = (&commitment.b * group.g.modpow(&t3, p) % p) * z.modpow(&t4, p) % p;
+
+    let epsilon = group.challenge(&alpha, &beta, &z, message);
+    let e = group.sub_mod_q(&group.sub_mod_q(&epsilon, &t2), &t4);
+
+    (e, UserState { t1, t2, t3, t4 })
+}
+
+/// Signer round 2: split the challenge as c = e - d and answer r = u - c x
+pub fn signer_respond(group: &Group, key: &SignerKey, state: SignerState, e: &BigUint) -> Response {
+    let c = group.sub_mod_q(e, &state.d);
+    let r = group.sub_mod_q(&state.u, &(&c * &key.x));
+
+    Response {
+        r,
+        c,
+        s: state.s,
+        d: state.d,
+    }
+}
+
+/// User round 2: check the signer's answer and shift it by the blinding values
+pub fn user_unblind(
+    group: &Group,
+    y: &BigUint,
+    info: &[u8],
+    commitment: &Commitment,
+    state: UserState,
+    response: &Response,
+) -> Option<Signature> {
+    let p = &group.p;
+    let z = group.hash_info(info);
+
+    // a == g^r y^c and b == g^s z^d
+    let a = group.g.modpow(&response.r, p) * y.modpow(&response.c, p) % p;
+    let b = group.g.modpow(&response.s, p) * z.modpow(&response.d, p) % p;
+    if a != commitment.a || b != commitment.b {
+        return None;
+    }
+
+    Some(Signature {
+        rho: (&response.r + state.t1) % &group.q,
+        omega: (&response.c + state.t2) % &group.q,
+        sigma: (&response.s + state.t3) % &group.q,
+        delta: (&response.d + state.t4) % &group.q,
+    })
+}
+
+/// Check omega + delta == H(g^rho y^omega || g^sigma z^delta || z || message) mod q, with every
+/// component reduced mod q
+pub fn verify(
+    group: &Group,
+    y: &BigUint,
+    info: &[u8],
+    message: &[u8],
+    signature: &Signature,
+) -> bool {
+    // Unreduced components would give a second valid signature, e.g. omega + q
+    let components = [
+        &signature.rho,
+        &signature.omega,
+        &signature.sigma,
+        &signature.delta,
+    ];
+    if components.iter().any(|component| *component >= &group.q) {
+        return false;
+    }
+
+    let p = &group.p;
+    let z = group.hash_info(info);
+
+    let alpha = group.g.modpow(&signature.rho, p) * y.modpow(&signature.omega, p) % p;
+    let beta = group.g.modpow(&signature.sigma, p) * z.modpow(&signature.delta, p) % p;
+
+    (&signature.omega + &signature.delta) % &group.q == group.challenge(&alpha, &beta, &z, message)
+}
+
+/// Public info binding a token to its expiry epoch
+fn expiry_info(expiry_epoch: u64) -> Vec<u8> {
+    format!("expires:{}", expiry_epoch).into_bytes()
+}
+
+/// A token is valid while the current epoch has not passed its expiry epoch
+fn verify_token(
+    group: &Group,
+    y: &BigUint,
+    expiry_epoch: u64,
+    current_epoch: u64,
+    message: &[u8],
+    signature: &Signature,
+) -> bool {
+    current_epoch <= expiry_epoch
+        && verify(group, y, &expiry_info(expiry_epoch), message, signature)
+}
+
+/// Entry point demonstrating expiring tokens with partially blind signatures
+pub fn run_demo() {
+    println!("\n--- Abe-Okamoto partially blind signatures ---\n");
+
+    let group = Group::modp_2048();
+    let key = SignerKey::generate(&group);
+
+    // Signer and user agree on the public info, the message stays hidden from the signer
+    let expiry_epoch = 42;
+    let info = expiry_info(expiry_epoch);
+    let message = b"token serial 7f3a";
+    println!("Public info: {}", String::from_utf8_lossy(&info));
+
+    let (commitment, signer_state) = signer_commit(&group, &info);
+    let (e, user_state) = user_blind(&group, &key.y, &info, message, &commitment);
+    let response = signer_respond(&group, &key, signer_state, &e);
+    let signature = user_unblind(&group, &key.y, &info, &commitment, user_state, &response)
+        .expect("Signer response is inconsistent");
+    println!("Signature omega: {}", signature.omega);
+
+    println!(
+        "Signature valid: {}",
+        verify(&group, &key.y, &info, message, &signature)
+    );
+    println!(
+        "Token valid in epoch 40: {}",
+        verify_token(&group, &key.y, expiry_epoch, 40, message, &signature)
+    );
+    println!(
+        "Token valid in epoch 43: {}",
+        verify_token(&group, &key.y, expiry_epoch, 43, message, &signature)
+    );
+
+    // Extending the expiry changes the info bound into the signature
+    println!(
+        "Token with expiry moved to epoch 100 valid: {}",
+        verify_token(&group, &key.y, 100, 43, message, &signature)
+    );
+
+    // The challenge the signer answered is unrelated to the one inside the signature
+    let epsilon = (&signature.omega + &signature.delta) % &group.q;
+    println!(
+        "Signer saw challenge e == signature challenge: {}",
+        e == epsilon
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run both rounds of the protocol for the given info and message
+    fn sign(group: &Group, key: &SignerKey, info: &[u8], message: &[u8]) -> Signature {
+        let (commitment, signer_state) = signer_commit(group, info);
+        let (e, user_state) = user_blind(group, &key.y, info, message, &commitment);
+        let response = signer_respond(group, key, signer_state, &e);
+        user_unblind(group, &key.y, info, &commitment, user_state, &response).unwrap()
+    }
+
+    #[test]
+    fn signature_is_bound_to_message_and_info() {
+        let group = Group::modp_2048();
+        let key = SignerKey::generate(&group);
+        let signature = sign(&group, &key, b"expires:42", b"token");
+
+        assert!(verify(&group, &key.y, b"expires:42", b"token", &signature));
+        assert!(!verify(
+            &group,
+            &key.y,
+            b"expires:100",
+            b"token",
+            &signature
+        ));
+        assert!(!verify(&group, &key.y, b"expires:42", b"other", &signature));
+
+        let mut unreduced = signature.clone();
+        unreduced.omega += &group.q;
+        assert!(!verify(&group, &key.y, b"expires:42", b"token", &unreduced));
+    }
+
+    #[test]
+    fn tampered_response_is_refused() {
+        let group = Group::modp_2048();
+        let key = SignerKey::generate(&group);
+        let info = b"expires:42";
+
+        let (commitment, signer_state) = signer_commit(&group, info);
+        let (e, user_state) = user_blind(&group, &key.y, info, b"token", &commitment);
+        let mut response = signer_respond(&group, &key, signer_state, &e);
+        response.r += 1u32;
+        assert!(user_unblind(&group, &key.y, info, &commitment, user_state, &response).is_none());
+    }
+
+    #[test]
+    fn token_expires_after_its_epoch() {
+        let group = Group::modp_2048();
+        let key = SignerKey::generate(&group);
+        let signature = sign(&group, &key, &expiry_info(42), b"token");
+
+        assert!(verify_token(&group, &key.y, 42, 40, b"token", &signature));
+        assert!(verify_token(&group, &key.y, 42, 42, b"token", &signature));
+        assert!(!verify_token(&group, &key.y, 42, 43, b"token", &signature));
+        // Moving the expiry changes the info the signature was made for
+        assert!(!verify_token(&group, &key.y, 100, 43, b"token", &signature));
+    }
+}
@@ -0,0 +1,44 @@
+use num_bigint_dig::{BigUint, ModInverse, RandBigInt};
+use num_integer::Integer;
+use num_traits::One;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+// Hash the message, the signer only ever sees a blinded H(m)
+pub fn hash_message(message: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(&Sha256::digest(message))
+}
+
+// User blinds H(m): m' = H(m) * r^e mod n, keeping r to unblind later
+pub fn blind(m_hash: &BigUint, n: &BigUint, e: &BigUint) -> (BigUint, BigUint) {
+    let mut rng = OsRng;
+
+    // Blinding factor r, such that gcd(r, n) = 1
+    let mut r;
+    loop {
+        r = rng.gen_biguint_below(n);
+        if r.gcd(n).is_one() {
+            break;
+        }
+    }
+
+    let re = r.modpow(e, n);
+    let m_blinded = (m_hash * &re) % n;
+    (m_blinded, r)
+}
+
+// Signer signs the blinded message: s' = (m')^d mod n
+pub fn sign_blinded(m_blinded: &BigUint, n: &BigUint, d: &BigUint) -> BigUint {
+    m_blinded.modpow(d, n)
+}
+
+// User removes the blinding factor: s = s' * r^{-1} mod n
+pub fn unblind(s_blinded: &BigUint, r: &BigUint, n: &BigUint) -> Option<BigUint> {
+    let r_inv = r.mod_inverse(n)?.to_biguint()?;
+    Some((s_blinded * &r_inv) % n)
+}
+
+// Check that s^e mod n == H(m)
+pub fn verify(m_hash: &BigUint, signature: &BigUint, n: &BigUint, e: &BigUint) -> bool {
+    &signature.modpow(e, n) == m_hash
+}
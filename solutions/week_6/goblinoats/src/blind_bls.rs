@@ -0,0 +1,266 @@
+use ark_bls12_381::{g1, Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::hashing::curve_maps::wb::WBMap;
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_ff::{Field, UniformRand, Zero};
+use rand::rngs::OsRng;
+use sha2::Sha256;
+
+// Domain separation tag for hashing messages to G1 (RFC 9380 suite for BLS12-381 G1)
+const DST: &[u8] = b"SCHOLA-BLIND-BLS-SIG-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+type G1Hasher =
+    MapToCurveBasedHasher<G1Projective, DefaultFieldHasher<Sha256, 128>, WBMap<g1::Config>>;
+
+/// Signer key pair: signatures live in G1 (48 bytes compressed), the public key in G2.
+pub struct KeyPair {
+    pub secret_key: Fr,
+    pub public_key: G2Affine,
+}
+
+impl KeyPair {
+    pub fn generate() -> Self {
+        let secret_key = Fr::rand(&mut OsRng);
+        let public_key = (G2Projective::generator() * secret_key).into_affine();
+        KeyPair {
+            secret_key,
+            public_key,
+        }
+    }
+
+    // Signer signs the blinded point: S' = sk * M'
+    pub fn sign_blinded(&self, blinded: &G1Affine) -> G1Affine {
+        (*blinded * self.secret_key).into_affine()
+    }
+}
+
+// Hash an arbitrary message to a point in G1
+pub fn hash_to_g1(message: &[u8]) -> G1Affine {
+    let hasher = G1Hasher::new(DST).expect("Failed to set up hash to G1");
+    hasher.hash(message).expect("Failed to hash message to G1")
+}
+
+// User blinds H(m) with a random scalar r: M' = r * H(m)
+pub fn blind(message: &[u8]) -> (G1Affine, Fr) {
+    let mut r = Fr::rand(&mut OsRng);
+    while r.is_zero() {
+        r = Fr::rand(&mut OsRng);
+    }
+    let blinded = (hash_to_g1(message) * r).into_affine();
+    (blinded, r)
+}
+
+// User removes the blinding factor: S = r^{-1} * S' = sk * H(m)
+pub fn unblind(blinded_signature: &G1Affine, r: &Fr) -> G1Affine {
+    let r_inv = r.inverse().expect("Blinding factor is non-zero");
+    (*blinded_signature * r_inv).into_affine()
+}
+
+// The user can check the signer's answer before unblinding: e(S', g2) == e(M', pk)
+pub fn verify_blinded(
+    blinded: &G1Affine,
+    blinded_signature: &G1Affine,
+    public_key: &G2Affine,
+) -> bool {
+    Bls12_381::pairing(blinded_signature, G2Affine::generator())
+        == Bls12_381::pairing(blinded, public_key)
+}
+
+// Check e(S, g2) == e(H(m), pk)
+pub fn verify(message: &[u8], signature: &G1Affine, public_key: &G2Affine) -> bool {
+    Bls12_381::pairing(signature, G2Affine::generator())
+        == Bls12_381::pairing(hash_to_g1(message), public_key)
+}
+
+// Verify many (message, signature, public key) triples with one multi-pairing.
+//
+// Each signature is weighted by a random scalar so that invalid signatures can't cancel out:
+// e(sum rho_i * S_i, g2) == prod e(rho_i * H(m_i), pk_i)
+pub fn batch_verify(items: &[(&[u8], G1Affine, G2Affine)]) -> bool {
+    if items.is_empty() {
+        return true;
+    }
+
+    let mut signature_sum = G1Projective::zero();
+    let mut g1_terms: Vec<G1Affine> = Vec::with_capacity(items.len() + 1);
+    let mut g2_terms: Vec<G2Affine> = Vec::with_capacity(items.len() + 1);
+
+    for (message, signature, public_key) in items {
+        let rho = Fr::rand(&mut OsRng);
+        signature_sum += *signature * rho;
+        g1_terms.push((hash_to_g1(message) * rho).into_affine());
+        g2_terms.push(*public_key);
+    }
+
+    // Move the signature side over: prod e(rho_i * H(m_i), pk_i) * e(-sum, g2) == 1
+    g1_terms.push((-signature_sum).into_affine());
+    g2_terms.push(G2Affine::generator());
+
+    Bls12_381::multi_pairing(g1_terms, g2_terms).is_zero()
+}
+
+// Aggregate signatures into a single G1 point: S = sum S_i
+pub fn aggregate(signatures: &[G1Affine]) -> G1Affine {
+    signatures
+        .iter()
+        .fold(G1Projective::zero(), |acc, signature| acc + signature)
+        .into_affine()
+}
+
+// Check e(S, g2) == prod e(H(m_i), pk_i).
+//
+// Messages have to be distinct, otherwise signatures on the same message under different keys
+// can be combined with rogue keys.
+pub fn verify_aggregate(
+    messages: &[&[u8]],
+    public_keys: &[G2Affine],
+    signature: &G1Affine,
+) -> bool {
+    if messages.is_empty() || messages.len() != public_keys.len() {
+        return false;
+    }
+    for (i, message) in messages.iter().enumerate() {
+        if messages[..i].contains(message) {
+            return false;
+        }
+    }
+
+    let mut g1_terms: Vec<G1Affine> = messages.iter().map(|message| hash_to_g1(message)).collect();
+    let mut g2_terms: Vec<G2Affine> = public_keys.to_vec();
+    g1_terms.push((-signature.into_group()).into_affine());
+    g2_terms.push(G2Affine::generator());
+
+    Bls12_381::multi_pairing(g1_terms, g2_terms).is_zero()
+}
+
+pub fn run_demo() {
+    println!("--- Blind BLS signatures over BLS12-381 ---");
+
+    let signer = KeyPair::generate();
+    let messages: Vec<&[u8]> = vec![b"token 1", b"token 2", b"token 3", b"token 4"];
+
+    let mut signatures = Vec::with_capacity(messages.len());
+    for message in &messages {
+        // User blinds, signer signs, user checks and unblinds
+        let (blinded, r) = blind(message);
+        let blinded_signature = signer.sign_blinded(&blinded);
+        assert!(verify_blinded(
+            &blinded,
+            &blinded_signature,
+            &signer.public_key
+        ));
+        let signature = unblind(&blinded_signature, &r);
+
+        println!(
+            "{}: signature valid = {}",
+            String::from_utf8_lossy(message),
+            verify(message, &signature, &signer.public_key)
+        );
+        signatures.push(signature);
+    }
+
+    let items: Vec<(&[u8], G1Affine, G2Affine)> = messages
+        .iter()
+        .zip(&signatures)
+        .map(|(message, signature)| (*message, *signature, signer.public_key))
+        .collect();
+    println!("Batch verification: {}", batch_verify(&items));
+
+    let aggregate_signature = aggregate(&signatures);
+    let public_keys = vec![signer.public_key; messages.len()];
+    println!(
+        "Aggregate of {} signatures valid: {}",
+        signatures.len(),
+        verify_aggregate(&messages, &public_keys, &aggregate_signature)
+    );
+    println!("Aggregate signature: {}", aggregate_signature);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blind_sign_unblind_verify() {
+        let signer = KeyPair::generate();
+        let (blinded, r) = blind(b"hello");
+        let signature = unblind(&signer.sign_blinded(&blinded), &r);
+
+        // The unblinded signature is the plain BLS signature on H(m)
+        assert_eq!(
+            signature,
+            (hash_to_g1(b"hello") * signer.secret_key).into_affine()
+        );
+        assert!(verify(b"hello", &signature, &signer.public_key));
+        assert!(!verify(b"goodbye", &signature, &signer.public_key));
+        assert!(!verify(
+            b"hello",
+            &signature,
+            &KeyPair::generate().public_key
+        ));
+    }
+
+    #[test]
+    fn batch_verification_catches_one_bad_signature() {
+        let signers = [KeyPair::generate(), KeyPair::generate()];
+        let messages: [&[u8]; 3] = [b"a", b"b", b"c"];
+        let mut items: Vec<(&[u8], G1Affine, G2Affine)> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| {
+                let signer = &signers[i % 2];
+                let (blinded, r) = blind(message);
+                (
+                    *message,
+                    unblind(&signer.sign_blinded(&blinded), &r),
+                    signer.public_key,
+                )
+            })
+            .collect();
+        assert!(batch_verify(&items));
+
+        items[1].1 = items[2].1;
+        assert!(!batch_verify(&items));
+    }
+
+    #[test]
+    fn aggregate_verifies_and_rejects_tampering() {
+        let signers = [
+            KeyPair::generate(),
+            KeyPair::generate(),
+            KeyPair::generate(),
+        ];
+        let messages: [&[u8]; 3] = [b"x", b"y", b"z"];
+        let signatures: Vec<G1Affine> = signers
+            .iter()
+            .zip(messages)
+            .map(|(signer, message)| {
+                let (blinded, r) = blind(message);
+                unblind(&signer.sign_blinded(&blinded), &r)
+            })
+            .collect();
+        let public_keys: Vec<G2Affine> = signers.iter().map(|signer| signer.public_key).collect();
+
+        let aggregate_signature = aggregate(&signatures);
+        assert!(verify_aggregate(
+            &messages,
+            &public_keys,
+            &aggregate_signature
+        ));
+        let wrong_message: [&[u8]; 3] = [b"x", b"y", b"w"];
+        let repeated_message: [&[u8]; 3] = [b"x", b"x", b"z"];
+        assert!(!verify_aggregate(
+            &wrong_message,
+            &public_keys,
+            &aggregate_signature
+        ));
+        assert!(!verify_aggregate(
+            &repeated_message,
+            &public_keys,
+            &aggregate_signature
+        ));
+    }
+}
@@ -0,0 +1,38 @@
+use config::{Config, ConfigError, File};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IssuerConfig {
+    pub port: u16,
+    pub key_bits: usize,
+    pub token_budget: u32,
+    pub rotation_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClientConfig {
+    pub issuer_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Settings {
+    pub issuer: IssuerConfig,
+    pub client: ClientConfig,
+}
+
+impl Settings {
+    pub fn new() -> Result<Self, ConfigError> {
+        let s = Config::builder()
+            .add_source(File::with_name("Config"))
+            .build()?;
+
+        let settings: Settings = s.try_deserialize()?;
+        // tokio's interval panics on a zero period
+        if settings.issuer.rotation_secs == 0 {
+            return Err(ConfigError::Message(
+                "issuer.rotation_secs must be at least 1".to_string(),
+            ));
+        }
+        Ok(settings)
+    }
+}
@@ -0,0 +1,379 @@
+use serde::{Serialize, Deserialize};
+use warp::Filter;
+use warp::http::StatusCode;
+use num_bigint_dig::BigUint;
+use rand::rngs::OsRng;
+use rsa::RsaPrivateKey;
+use rsa::traits::{PublicKeyParts, PrivateKeyParts};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::time::{interval, Duration};
+
+use crate::blind;
+use crate::config::IssuerConfig;
+
+
+#[derive(Debug)]
+pub struct InvalidRequest;
+
+impl warp::reject::Reject for InvalidRequest {}
+
+#[derive(Debug)]
+pub struct UnknownKey;
+
+impl warp::reject::Reject for UnknownKey {}
+
+#[derive(Debug)]
+pub struct KeyRetired;
+
+impl warp::reject::Reject for KeyRetired {}
+
+#[derive(Debug)]
+pub struct BudgetExhausted;
+
+impl warp::reject::Reject for BudgetExhausted {}
+
+// Public key published by the issuer, retired keys stay listed so old tokens still verify
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublicKeyResponse {
+    pub key_id: u32,
+    pub n: BigUint,
+    pub e: BigUint,
+    pub active: bool,
+}
+
+// Blinded message sent by the client. The issuer takes `client_id` on trust, there is no
+// authentication, so a client can pick a fresh ID per request and the budget is advisory only.
+#[derive(Serialize, Deserialize)]
+struct SignRequest {
+    client_id: String,
+    key_id: u32,
+    blinded_message: BigUint,
+}
+
+// Blinded signature returned by the issuer
+#[derive(Serialize, Deserialize)]
+struct SignResponse {
+    key_id: u32,
+    blinded_signature: BigUint,
+    remaining: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+// A signature on H(m) together with the ID of the key that produced it
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub key_id: u32,
+    pub signature: BigUint,
+}
+
+struct IssuerKey {
+    id: u32,
+    private_key: RsaPrivateKey,
+}
+
+impl IssuerKey {
+    fn generate(id: u32, bits: usize) -> Self {
+        let private_key = RsaPrivateKey::new(&mut OsRng, bits).expect("Failed to generate RSA key");
+        IssuerKey { id, private_key }
+    }
+
+    fn public(&self, active: bool) -> PublicKeyResponse {
+        PublicKeyResponse {
+            key_id: self.id,
+            n: self.private_key.n().clone(),
+            e: self.private_key.e().clone(),
+            active,
+        }
+    }
+}
+
+// Issuer state, the last key is the active one
+struct IssuerState {
+    keys: Vec<IssuerKey>,
+    // Tokens issued per (claimed client ID, key): budgets refill whenever the key rotates.
+    // This only limits honest clients, see SignRequest
+    issued: HashMap<(String, u32), u32>,
+    token_budget: u32,
+}
+
+impl IssuerState {
+    fn active_key(&self) -> &IssuerKey {
+        self.keys.last().expect("Issuer has no keys")
+    }
+}
+
+// Issuer
+pub async fn run_issuer(config: IssuerConfig) {
+    let (addr, server) = bind_issuer(config);
+    println!("Issuer running on http://{}", addr);
+    server.await;
+}
+
+// Bind the issuer to its configured port (0 picks a free one) and return the server future
+pub fn bind_issuer(config: IssuerConfig) -> (SocketAddr, impl Future<Output = ()>) {
+    let state = Arc::new(Mutex::new(IssuerState {
+        keys: vec![IssuerKey::generate(1, config.key_bits)],
+        issued: HashMap::new(),
+        token_budget: config.token_budget,
+    }));
+
+    let rotation = rotate_keys(state.clone(), config.key_bits, config.rotation_secs);
+    let state = warp::any().map(move || state.clone());
+
+    let keys = warp::get()
+        .and(warp::path("keys"))
+        .and(warp::path::end())
+        .and(state.clone())
+        .and_then(handle_keys);
+
+    let current_key = warp::get()
+        .and(warp::path!("keys" / "current"))
+        .and(state.clone())
+        .and_then(handle_current_key);
+
+    let sign = warp::post()
+        .and(warp::path("sign"))
+        .and(warp::body::json())
+        .and(state)
+        .and_then(handle_sign);
+
+    let routes = keys.or(current_key).or(sign).recover(handle_rejection);
+
+    let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], config.port));
+    let server = async move {
+        tokio::spawn(rotation);
+        server.await
+    };
+    (addr, server)
+}
+
+// Replace the active key every `rotation_secs`
+async fn rotate_keys(state: Arc<Mutex<IssuerState>>, key_bits: usize, rotation_secs: u64) {
+    let mut ticker = interval(Duration::from_secs(rotation_secs));
+    ticker.tick().await; // The first tick completes immediately
+
+    loop {
+        ticker.tick().await;
+        let next_id = state.lock().await.active_key().id + 1;
+        let key = tokio::task::spawn_blocking(move || IssuerKey::generate(next_id, key_bits))
+            .await
+            .expect("Key generation task panicked");
+        state.lock().await.keys.push(key);
+        println!("Rotated to key {}", next_id);
+    }
+}
+
+async fn handle_keys(state: Arc<Mutex<IssuerState>>) -> Result<impl warp::Reply, warp::Rejection> {
+    let state = state.lock().await;
+    let active_id = state.active_key().id;
+    let keys: Vec<PublicKeyResponse> = state
+        .keys
+        .iter()
+        .map(|key| key.public(key.id == active_id))
+        .collect();
+    Ok(warp::reply::json(&keys))
+}
+
+async fn handle_current_key(
+    state: Arc<Mutex<IssuerState>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let state = state.lock().await;
+    Ok(warp::reply::json(&state.active_key().public(true)))
+}
+
+async fn handle_sign(
+    body: serde_json::Value,
+    state: Arc<Mutex<IssuerState>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let request: SignRequest = serde_json::from_value(body).map_err(|_| warp::reject::custom(InvalidRequest))?;
+    let mut state = state.lock().await;
+
+    // Only the active key signs, retired keys are kept for verification only
+    let key = state.active_key();
+    if key.id != request.key_id {
+        if state.keys.iter().any(|k| k.id == request.key_id) {
+            return Err(warp::reject::custom(KeyRetired));
+        }
+        return Err(warp::reject::custom(UnknownKey));
+    }
+    let n = key.private_key.n().clone();
+    let d = key.private_key.d().clone();
+    if request.blinded_message >= n {
+        return Err(warp::reject::custom(InvalidRequest));
+    }
+
+    let budget = state.token_budget;
+    let issued = state.issued.entry((request.client_id, request.key_id)).or_insert(0);
+    if *issued >= budget {
+        return Err(warp::reject::custom(BudgetExhausted));
+    }
+    *issued += 1;
+
+    let response = SignResponse {
+        key_id: request.key_id,
+        blinded_signature: blind::sign_blinded(&request.blinded_message, &n, &d),
+        remaining: budget - *issued,
+    };
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, Infallible> {
+    let (code, message) = if err.find::<BudgetExhausted>().is_some() {
+        (StatusCode::TOO_MANY_REQUESTS, "token budget exhausted")
+    } else if err.find::<KeyRetired>().is_some() {
+        (StatusCode::CONFLICT, "key has been rotated")
+    } else if err.find::<UnknownKey>().is_some() {
+        (StatusCode::NOT_FOUND, "unknown key")
+    } else if err.find::<InvalidRequest>().is_some()
+        || err.find::<warp::filters::body::BodyDeserializeError>().is_some()
+    {
+        (StatusCode::BAD_REQUEST, "invalid request")
+    } else if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "not found")
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+    };
+
+    let body = warp::reply::json(&ErrorResponse { error: message.to_string() });
+    Ok(warp::reply::with_status(body, code))
+}
+
+// Client
+pub struct Client {
+    id: String,
+    issuer_url: String,
+    http: reqwest::Client,
+}
+
+impl Client {
+    pub fn new(id: String, issuer_url: String) -> Self {
+        Self { id, issuer_url, http: reqwest::Client::new() }
+    }
+
+    pub async fn current_key(&self) -> Result<PublicKeyResponse, Box<dyn Error>> {
+        let response = self.http.get(format!("{}/keys/current", self.issuer_url)).send().await?;
+        parse_response(response).await
+    }
+
+    pub async fn public_keys(&self) -> Result<Vec<PublicKeyResponse>, Box<dyn Error>> {
+        let response = self.http.get(format!("{}/keys", self.issuer_url)).send().await?;
+        parse_response(response).await
+    }
+
+    // Blind H(message), have the issuer sign it, unblind and check the result
+    pub async fn request_token(&self, message: &[u8]) -> Result<Token, Box<dyn Error>> {
+        let m_hash = blind::hash_message(message);
+
+        // The key can rotate between fetching it and signing, so retry once with the new key
+        for _ in 0..2 {
+            let key = self.current_key().await?;
+            let (blinded_message, r) = blind::blind(&m_hash, &key.n, &key.e);
+
+            let request = SignRequest {
+                client_id: self.id.clone(),
+                key_id: key.key_id,
+                blinded_message,
+            };
+            let response = self
+                .http
+                .post(format!("{}/sign", self.issuer_url))
+                .json(&request)
+                .send()
+                .await?;
+            if response.status() == StatusCode::CONFLICT {
+                continue;
+            }
+            let response: SignResponse = parse_response(response).await?;
+
+            let signature = blind::unblind(&response.blinded_signature, &r, &key.n)
+                .ok_or("Failed to compute modular inverse")?;
+            if !blind::verify(&m_hash, &signature, &key.n, &key.e) {
+                return Err("Issuer returned an invalid signature".into());
+            }
+            println!("Token issued by key {}, {} left in budget", response.key_id, response.remaining);
+
+            return Ok(Token { key_id: response.key_id, signature });
+        }
+
+        Err("Issuer keeps rotating keys".into())
+    }
+
+    // Verify a token against the published key it names
+    pub async fn verify_token(&self, message: &[u8], token: &Token) -> Result<bool, Box<dyn Error>> {
+        let keys = self.public_keys().await?;
+        let key = keys
+            .iter()
+            .find(|key| key.key_id == token.key_id)
+            .ok_or("Token was signed with an unknown key")?;
+
+        Ok(blind::verify(&blind::hash_message(message), &token.signature, &key.n, &key.e))
+    }
+}
+
+// Turn non-success responses into errors carrying the issuer's message
+async fn parse_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T, Box<dyn Error>> {
+    let status = response.status();
+    if !status.is_success() {
+        let error: ErrorResponse = response.json().await?;
+        return Err(format!("Issuer rejected request ({}): {}", status, error.error).into());
+    }
+    Ok(response.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(token_budget: u32, rotation_secs: u64) -> IssuerConfig {
+        IssuerConfig { port: 0, key_bits: 1024, token_budget, rotation_secs }
+    }
+
+    #[tokio::test]
+    async fn issue_until_budget_exhausted() {
+        let (addr, server) = bind_issuer(test_config(2, 3600));
+        tokio::spawn(server);
+        let url = format!("http://{}", addr);
+
+        let alice = Client::new("alice".to_string(), url.clone());
+        for message in [b"token 1", b"token 2"] {
+            let token = alice.request_token(message).await.unwrap();
+            assert!(alice.verify_token(message, &token).await.unwrap());
+            assert!(!alice.verify_token(b"other", &token).await.unwrap());
+        }
+        assert!(alice.request_token(b"token 3").await.is_err());
+
+        // Budgets are tracked per client
+        let bob = Client::new("bob".to_string(), url);
+        assert!(bob.request_token(b"token 1").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn old_tokens_verify_after_rotation() {
+        let (addr, server) = bind_issuer(test_config(1, 1));
+        tokio::spawn(server);
+        let client = Client::new("alice".to_string(), format!("http://{}", addr));
+
+        let token = client.request_token(b"before").await.unwrap();
+        while client.current_key().await.unwrap().key_id == token.key_id {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        // The new key comes with a fresh budget, and the retired key still verifies
+        let new_token = client.request_token(b"after").await.unwrap();
+        assert_ne!(new_token.key_id, token.key_id);
+        assert!(client.verify_token(b"before", &token).await.unwrap());
+        assert!(client.verify_token(b"after", &new_token).await.unwrap());
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{Field, One, UniformRand, Zero};
+use rand::rngs::OsRng;
+
+use crate::blind_bls::{hash_to_g1, verify};
+
+/// A holder's share of the signing key: the point (index, f(index)) on the dealer's polynomial.
+#[derive(Clone, Debug)]
+pub struct SecretKeyShare {
+    pub index: u64,
+    pub secret: Fr,
+}
+
+/// Everything that is public after dealing: the group key g2^f(0) and g2^f(i) for every holder.
+#[derive(Clone, Debug)]
+pub struct ThresholdPublicKey {
+    pub threshold: usize,
+    pub group_key: G2Affine,
+    pub holder_keys: Vec<G2Affine>,
+}
+
+impl ThresholdPublicKey {
+    // Holder indices start at 1, the group key sits at 0
+    pub fn holder_key(&self, index: u64) -> Option<&G2Affine> {
+        let position = usize::try_from(index).ok()?.checked_sub(1)?;
+        self.holder_keys.get(position)
+    }
+}
+
+/// One holder's signature share sk_i * H(m).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialSignature {
+    pub index: u64,
+    pub signature: G1Affine,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ThresholdError {
+    NotEnoughPartials { have: usize, need: usize },
+    DuplicateIndex(u64),
+    UnknownIndex(u64),
+}
+
+impl fmt::Display for ThresholdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThresholdError::NotEnoughPartials { have, need } => {
+                write!(
+                    f,
+                    "{} partial signatures given but {} are needed",
+                    have, need
+                )
+            }
+            ThresholdError::DuplicateIndex(index) => {
+                write!(f, "more than one partial signature from holder {}", index)
+            }
+            ThresholdError::UnknownIndex(index) => write!(f, "there is no holder {}", index),
+        }
+    }
+}
+
+impl std::error::Error for ThresholdError {}
+
+// Dealer picks f of degree t - 1 over Fr with f(0) = sk and hands holder i the share f(i)
+pub fn deal(threshold: usize, holders: usize) -> (ThresholdPublicKey, Vec<SecretKeyShare>) {
+    assert!(
+        threshold >= 1 && threshold <= holders,
+        "threshold must be between 1 and the number of holders"
+    );
+    let coefficients: Vec<Fr> = (0..threshold).map(|_| Fr::rand(&mut OsRng)).collect();
+
+    let shares: Vec<SecretKeyShare> = (1..=holders as u64)
+        .map(|index| {
+            // Horner's rule from the highest coefficient down
+            let x = Fr::from(index);
+            let secret = coefficients
+                .iter()
+                .rev()
+                .fold(Fr::zero(), |acc, coefficient| acc * x + coefficient);
+            SecretKeyShare { index, secret }
+        })
+        .collect();
+
+    let public_key = ThresholdPublicKey {
+        threshold,
+        group_key: (G2Projective::generator() * coefficients[0]).into_affine(),
+        holder_keys: shares
+            .iter()
+            .map(|share| (G2Projective::generator() * share.secret).into_affine())
+            .collect(),
+    };
+    (public_key, shares)
+}
+
+// Holder i signs with its share: S_i = f(i) * H(m)
+pub fn sign_partial(share: &SecretKeyShare, message: &[u8]) -> PartialSignature {
+    PartialSignature {
+        index: share.index,
+        signature: (hash_to_g1(message) * share.secret).into_affine(),
+    }
+}
+
+// A partial is an ordinary BLS signature under the holder's key: e(S_i, g2) == e(H(m), g2^f(i))
+pub fn verify_partial(
+    public_key: &ThresholdPublicKey,
+    message: &[u8],
+    partial: &PartialSignature,
+) -> bool {
+    public_key
+        .holder_key(partial.index)
+        .is_some_and(|holder_key| verify(message, &partial.signature, holder_key))
+}
+
+// Lagrange coefficients at x = 0 for distinct non-zero indices:
+// l_i = prod_{j != i} x_j / (x_j - x_i)
+pub fn lagrange_coefficients(indices: &[u64]) -> Vec<Fr> {
+    let xs: Vec<Fr> = indices.iter().map(|&index| Fr::from(index)).collect();
+    xs.iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            let (numerator, denominator) = xs
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold((Fr::one(), Fr::one()), |(num, den), (_, x_j)| {
+                    (num * x_j, den * (*x_j - x_i))
+                });
+            numerator * denominator.inverse().expect("Indices are distinct")
+        })
+        .collect()
+}
+
+// Interpolate in the exponent: S = sum l_i * S_i = f(0) * H(m), using the first t partials.
+//
+// The partials are not checked here, run verify_partial first or a single bad one spoils S.
+pub fn combine(
+    public_key: &ThresholdPublicKey,
+    partials: &[PartialSignature],
+) -> Result<G1Affine, ThresholdError> {
+    let need = public_key.threshold;
+    if partials.len() < need {
+        return Err(ThresholdError::NotEnoughPartials {
+            have: partials.len(),
+            need,
+        });
+    }
+    let selected = &partials[..need];
+    let mut seen = HashSet::new();
+    for partial in selected {
+        if public_key.holder_key(partial.index).is_none() {
+            return Err(ThresholdError::UnknownIndex(partial.index));
+        }
+        if !seen.insert(partial.index) {
+            return Err(ThresholdError::DuplicateIndex(partial.index));
+        }
+    }
+
+    let indices: Vec<u64> = selected.iter().map(|partial| partial.index).collect();
+    let signature = selected
+        .iter()
+        .zip(lagrange_coefficients(&indices))
+        .fold(G1Projective::zero(), |acc, (partial, coefficient)| {
+            acc + partial.signature * coefficient
+        });
+    Ok(signature.into_affine())
+}
+
+pub fn run_demo() {
+    println!("--- 3-of-5 threshold BLS over BLS12-381 ---");
+
+    let (public_key, shares) = deal(3, 5);
+    let message: &[u8] = b"release v1.0.0";
+
+    // Holder 2 is faulty and signs the wrong message
+    let mut partials: Vec<PartialSignature> = shares
+        .iter()
+        .map(|share| sign_partial(share, message))
+        .collect();
+    partials[1] = sign_partial(&shares[1], b"release v6.6.6");
+
+    let valid: Vec<PartialSignature> = partials
+        .iter()
+        .filter(|partial| {
+            let ok = verify_partial(&public_key, message, partial);
+            println!("Partial from holder {} valid: {}", partial.index, ok);
+            ok
+        })
+        .copied()
+        .collect();
+
+    // Any 3 valid partials give the same signature under the group key
+    let signature = combine(&public_key, &valid).expect("Enough valid partials");
+    let other = combine(&public_key, &valid[1..]).expect("Enough valid partials");
+    println!(
+        "Combined signature valid under the group key: {}",
+        verify(message, &signature, &public_key.group_key)
+    );
+    println!(
+        "Different subsets give the same signature: {}",
+        signature == other
+    );
+
+    if let Err(error) = combine(&public_key, &valid[..2]) {
+        println!("Combining 2 partials: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_threshold_of_partials_combine() {
+        let (public_key, shares) = deal(3, 5);
+        let message: &[u8] = b"hello";
+        let partials: Vec<PartialSignature> = shares
+            .iter()
+            .map(|share| sign_partial(share, message))
+            .collect();
+        assert!(partials
+            .iter()
+            .all(|partial| verify_partial(&public_key, message, partial)));
+
+        let signature = combine(&public_key, &partials[2..]).unwrap();
+        assert!(verify(message, &signature, &public_key.group_key));
+        let reordered = [partials[4], partials[0], partials[3]];
+        assert_eq!(combine(&public_key, &reordered), Ok(signature));
+
+        // A partial for another message fails its own check and spoils the combination
+        let forged = sign_partial(&shares[0], b"goodbye");
+        assert!(!verify_partial(&public_key, message, &forged));
+        let spoiled = combine(&public_key, &[forged, partials[1], partials[2]]).unwrap();
+        assert!(!verify(message, &spoiled, &public_key.group_key));
+
+        assert_eq!(
+            combine(&public_key, &[partials[0], partials[0], partials[1]]),
+            Err(ThresholdError::DuplicateIndex(1))
+        );
+        assert_eq!(
+            combine(&public_key, &partials[..2]),
+            Err(ThresholdError::NotEnoughPartials { have: 2, need: 3 })
+        );
+    }
+}